@@ -0,0 +1,22 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+
+use crate::states::app_state::SharedAppState;
+use crate::states::replay_state::Replay;
+
+async fn get_replay(Path(replay_id): Path<String>, State(state): State<SharedAppState>) -> Result<Json<Replay>, StatusCode> {
+    let state = state.lock().await;
+
+    match state.replays.get_replay(&replay_id).await {
+        Some(replay) => Ok(Json(replay)),
+        None => Err(StatusCode::NOT_FOUND)
+    }
+}
+
+pub fn router(state: SharedAppState) -> Router {
+    Router::new()
+        .route("/replays/:replay_id", get(get_replay))
+        .with_state(state)
+}
@@ -1,25 +1,40 @@
 pub mod states;
 pub mod text;
+mod http_routes;
 mod socket_events;
 mod util;
 
+use std::sync::Arc;
 use socketioxide::SocketIo;
+use tokio::sync::Mutex;
 use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
 use tracing::info;
 use tracing_subscriber::FmtSubscriber;
-use crate::states::app_state::SharedAppState;
+use crate::states::app_state::{AppState, SharedAppState};
+use crate::text::code::{SnippetStore, DEFAULT_SNIPPET_DIR};
+use crate::text::pool::refill_text_pool;
+use crate::text::wikipedia::WikipediaClient;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing::subscriber::set_global_default(FmtSubscriber::default())?;
 
-    let app_state = SharedAppState::default();
-    let (layer, io) = SocketIo::builder().with_state(app_state).build_layer();
+    let mut app_state = AppState::default();
+    if let Ok(base_url) = std::env::var("WIKIPEDIA_API_URL") {
+        app_state.wikipedia = WikipediaClient::new(base_url);
+    }
+
+    let snippet_dir = std::env::var("CODE_SNIPPETS_DIR").unwrap_or(DEFAULT_SNIPPET_DIR.to_string());
+    app_state.snippets = SnippetStore::load(snippet_dir);
+
+    let app_state: SharedAppState = Arc::new(Mutex::new(app_state));
+    tokio::spawn(refill_text_pool(app_state.clone()));
+    let (layer, io) = SocketIo::builder().with_state(app_state.clone()).build_layer();
     
     io.ns("/", socket_events::handle_websocket_connection);
 
-    let app = axum::Router::new()
+    let app = http_routes::router(app_state)
         .layer(
             ServiceBuilder::new()
                 .layer(CorsLayer::permissive())
@@ -1,33 +1,95 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::ops::{Sub};
 use std::time::{Duration, SystemTime};
 use serde::{Deserialize, Serialize};
 use socketioxide::extract::{Data, SocketRef, State};
 use tokio::sync::MutexGuard;
 use tokio::time::sleep;
-use tracing::{info};
+use tracing::{info, warn};
 use uuid::Uuid;
 use crate::states::app_state::{AppState, SharedAppState};
-use crate::states::game_state::{GameState, TEXT_SIZE};
+use crate::states::game_state::{Availability, ChatMessage, GameMode, GameSettings, GameState, LateJoinPolicy, SeriesStandings};
+use crate::states::replay_state::{Keystroke, Replay};
 use crate::states::user_state::UserInfo;
-use crate::text::wikipedia::{get_pretty_extract, get_random_article_extract};
+use crate::text::cache::{Passage, TextSource};
+use crate::text::custom::clean_custom_texts;
+use crate::text::pool::take_or_generate_text;
+use crate::text::quality::TextQuality;
+use crate::text::wikipedia::Language;
 use crate::util::user_color::UserColor;
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Character {
-    character: char,
-    index: usize
+#[derive(Debug, Deserialize)]
+struct CreateGameInfo {
+    name: String,
+    #[serde(default)]
+    color: Option<UserColor>,
+    #[serde(default)]
+    settings: GameSettings
 }
 
 #[derive(Debug, Serialize)]
 struct UserConnectData {
     user_map: HashMap<String, String>,
+    spectator_map: HashMap<String, String>,
+    late_users: Vec<String>,
     correct_text_length_map: HashMap<String, usize>,
     app_state: GameState,
     color: HashMap<String, UserColor>,
-    finished_generating_text: bool
+    finished_generating_text: bool,
+    host: String,
+    ready_map: HashMap<String, bool>,
+    chat_history: VecDeque<ChatMessage>,
+    muted_users: Vec<String>,
+    passage_id: String,
+    text_quality: Option<TextQuality>,
+    settings: GameSettings,
+    standings: SeriesStandings,
+    team_map: HashMap<String, usize>
+}
+
+#[derive(Debug, Deserialize)]
+struct GhostRaceInfo {
+    name: String,
+    replay_id: String,
+    user_id: String
+}
+
+#[derive(Debug, Deserialize)]
+struct WatchReplayInfo {
+    replay_id: String,
+    #[serde(default = "default_replay_speed")]
+    speed: f64
+}
+
+fn default_replay_speed() -> f64 {
+    1.0
 }
 
+#[derive(Serialize)]
+struct ReplayStartOut {
+    replay_id: String,
+    text: String,
+    user_map: HashMap<String, String>,
+    color: HashMap<String, UserColor>
+}
+
+#[derive(Debug, Deserialize)]
+struct MuteInfo {
+    user_id: String,
+    muted: bool
+}
+
+#[derive(Debug, Deserialize)]
+struct TeamAssignment {
+    user_id: String,
+    team: usize
+}
+
+const GAME_COUNTDOWN: u64 = 5;
+const PRACTICE_COUNTDOWN: u64 = 3;
+const MIN_REPLAY_SPEED: f64 = 0.25;
+const MAX_REPLAY_SPEED: f64 = 4.0;
+
 #[derive(Serialize)]
 struct UserTextChangeOut {
     user_id: String,
@@ -35,18 +97,23 @@ struct UserTextChangeOut {
 }
 
 async fn user_join<'a>(socket: &SocketRef, user: &UserInfo, state: &MutexGuard<'a, AppState>) -> bool {
+    if state.users.get_user(&socket.id.to_string()).await.is_some_and(|current| current.room != user.room) {
+        user_leave(socket, state).await;
+    }
+
     state.users.add_user(socket.id.to_string(), user.clone()).await;
     
     let _ = socket.leave_all();
     let _ = socket.join(user.room.clone());
 
-    let game_create = state.games.init_game(user.room.clone()).await;
-    state.games.add_user(socket.id.to_string(), user.name.clone(), &user.room).await;
+    let game_create = state.games.init_game(user.room.clone(), GameSettings::default()).await;
+    state.games.add_user(socket.id.to_string(), user.name.clone(), user.color, &user.room).await;
 
     let data = get_game_data(&user.room, state).await;
 
     if data.app_state == GameState::Game {
         let _ = socket.emit("start_game", state.games.get_game_text(&user.room).await);
+        let _ = socket.emit("text_metadata", state.games.get_sources(&user.room).await);
     }
     
     let _ = socket.within(user.room.clone()).emit("user_connect", data);
@@ -54,36 +121,357 @@ async fn user_join<'a>(socket: &SocketRef, user: &UserInfo, state: &MutexGuard<'
     game_create
 }
 
-async fn user_leave<'a>(socket: &SocketRef, state: &MutexGuard<'a, AppState>) {
-    let user_id = socket.id.to_string();
+async fn spectator_join<'a>(socket: &SocketRef, user: &UserInfo, state: &MutexGuard<'a, AppState>) {
+    if state.users.get_user(&socket.id.to_string()).await.is_some_and(|current| current.room != user.room) {
+        user_leave(socket, state).await;
+    }
 
-    if let Some(user) = state.users.remove_user(&user_id).await {
-        let game_delete = state.games.remove_user(&user.room, &user_id).await;
+    state.users.add_user(socket.id.to_string(), user.clone()).await;
 
-        if !game_delete {
-            let _ = socket.within(user.room.clone()).emit("user_connect", get_game_data(&user.room, state).await);
+    let _ = socket.leave_all();
+    let _ = socket.join(user.room.clone());
+
+    state.games.add_spectator(socket.id.to_string(), user.name.clone(), &user.room).await;
+
+    let data = get_game_data(&user.room, state).await;
+
+    if data.app_state == GameState::Game {
+        let _ = socket.emit("start_game", state.games.get_game_text(&user.room).await);
+        let _ = socket.emit("text_metadata", state.games.get_sources(&user.room).await);
+    }
+
+    let _ = socket.within(user.room.clone()).emit("user_connect", data);
+    info!("Client: {} is spectating!", socket.id.to_string());
+}
+
+async fn apply_late_join_policy<'a>(socket: &SocketRef, user: &UserInfo, state: &MutexGuard<'a, AppState>) -> bool {
+    if !state.games.has_started(&user.room).await {
+        return false;
+    }
+
+    match state.games.get_settings(&user.room).await.late_join {
+        LateJoinPolicy::Racer => false,
+        LateJoinPolicy::Spectate if state.games.is_spectatable(&user.room).await => {
+            info!("User: {} spectates the room: {}", user.name, user.room);
+            spectator_join(socket, user, state).await;
+
+            let _ = socket.emit("allowed_to_spectate", "");
+            true
+        }
+        LateJoinPolicy::Spectate | LateJoinPolicy::Block => {
+            let _ = socket.emit("game_unavailable", "");
+            true
         }
-        info!("Client: {} left!", socket.id.to_string());
     }
 }
 
-async fn create_game<'a>(socket: &SocketRef, state: &MutexGuard<'a, AppState>, game_id: String, user: &mut UserInfo) {
+async fn user_leave<'a>(socket: &SocketRef, state: &MutexGuard<'a, AppState>) -> Option<String> {
+    let user_id = socket.id.to_string();
+    let user = state.users.remove_user(&user_id).await?;
+    let game_delete = state.games.remove_user(&user.room, &user_id).await;
+    info!("Client: {} left!", socket.id.to_string());
+
+    if game_delete {
+        return None;
+    }
+
+    let _ = socket.within(user.room.clone()).emit("user_connect", get_game_data(&user.room, state).await);
+    Some(user.room)
+}
+
+async fn create_game<'a>(socket: &SocketRef, state: &MutexGuard<'a, AppState>, game_id: String, user: &mut UserInfo, settings: GameSettings) {
     user.room.clone_from(&game_id);
     let room = user.room.clone();
 
     info!("User: {} created the room: {}", user.name, room);
+    state.games.init_game(room, settings).await;
     user_join(socket, user, state).await;
 
     let _ = socket.emit("game_id", game_id);
 }
 
+async fn finish_game<'a>(socket: &SocketRef, room: &String, state: &MutexGuard<'a, AppState>) {
+    info!("The Game {} has finished", room);
+    let _ = socket.within(room.clone()).emit("app_state_change", state.games.get_game_state(room).await);
+    let _ = socket.within(room.clone()).emit("game_results", state.games.get_placements(room).await);
+
+    if state.games.get_settings(room).await.teams.is_some() {
+        let _ = socket.within(room.clone()).emit("team_results", state.games.get_team_placements(room).await);
+    }
+
+    let _ = socket.within(room.clone()).emit("text_metadata", state.games.get_sources(room).await);
+
+    let replay_id = state.replays.add_replay(state.games.get_replay(room).await).await;
+    let _ = socket.within(room.clone()).emit("replay_id", replay_id);
+
+    if state.games.get_settings(room).await.rounds > 1 {
+        let _ = socket.within(room.clone()).emit("series_standings", state.games.award_points(room).await);
+    }
+
+    if state.games.get_settings(room).await.practice {
+        let _ = socket.within(room.clone()).emit("practice_results", state.games.get_user_stats(room).await);
+    }
+}
+
+async fn emit_team_progress<'a>(socket: &SocketRef, room: &String, state: &MutexGuard<'a, AppState>) {
+    let team_progress = state.games.get_team_progress(room).await;
+
+    if !team_progress.is_empty() {
+        let _ = socket.within(room.clone()).emit("team_progress", team_progress);
+    }
+}
+
+async fn run_time_limit(socket: &SocketRef, state: &SharedAppState, room: &String) {
+    loop {
+        let state_guard = state.lock().await;
+
+        match state_guard.games.get_time_remaining(room).await {
+            Some(0) => {
+                state_guard.games.end_game(room).await;
+                finish_game(socket, room, &state_guard).await;
+                break;
+            }
+            Some(seconds) => {
+                let _ = socket.within(room.clone()).emit("time_remaining", seconds);
+            }
+            None => break
+        }
+
+        drop(state_guard);
+        sleep(Duration::from_secs(1)).await;
+    }
+}
+
+async fn generate_room_text(socket: &SocketRef, state: &SharedAppState, room: &String) {
+    let state_guard = state.lock().await;
+
+    if !state_guard.games.started_generating_text(room).await {
+        state_guard.games.set_start_generating_text(room).await;
+        let generation = state_guard.games.get_text_generation(room).await;
+
+        let custom_passage = match state_guard.games.take_custom_passage(room).await {
+            Some(passage_id) => state_guard.passages.get_passage(&passage_id).await,
+            None => None
+        };
+
+        let settings = state_guard.games.get_settings(room).await;
+        let players = state_guard.games.get_player_names(room).await;
+        drop(state_guard);
+
+        let passage = match custom_passage {
+            Some(passage) => passage,
+            None => next_passage(state, &settings, &players).await
+        };
+
+        let state_guard = state.lock().await;
+        if !state_guard.games.set_generated_passage(room, passage, generation).await {
+            return;
+        }
+    } else {
+        drop(state_guard);
+    }
+
+    let _ = socket.within(room.clone()).emit("created_game_text", true);
+    let _ = socket.within(room.clone()).emit("text_quality", state.lock().await.games.get_quality(room).await);
+    check_auto_start(socket, state, room).await;
+}
+
+async fn next_passage(state: &SharedAppState, settings: &GameSettings, players: &[String]) -> Passage {
+    if settings.mode == GameMode::Code {
+        let state_guard = state.lock().await;
+
+        if let Some(snippet) = state_guard.snippets.random_snippet(settings.code_language.as_deref()) {
+            let title = format!("{} ({})", snippet.title, snippet.language);
+            return state_guard.passages.add_passage(TextSource::Code, title, Language::default(), Some(snippet.language), snippet.text).await;
+        }
+
+        warn!("No code snippets available, falling back to generated text");
+    }
+
+    take_or_generate_text(state, settings.language, settings.text_length, players).await
+}
+
+async fn check_auto_start(socket: &SocketRef, state: &SharedAppState, room: &String) {
+    let state_guard = state.lock().await;
+
+    if state_guard.games.should_auto_start(room).await {
+        info!("The game in the room {} was started automatically!", room);
+        start_race(socket, state, state_guard, room).await;
+    }
+}
+
+async fn run_lobby_timer(socket: SocketRef, state: SharedAppState, room: String, seconds: u64) {
+    sleep(Duration::from_secs(seconds)).await;
+
+    loop {
+        let state_guard = state.lock().await;
+        if state_guard.games.find_game_state(&room).await != Some(GameState::Lobby) {
+            return;
+        }
+
+        if state_guard.games.finished_generating_text(&room).await {
+            drop(state_guard);
+            check_auto_start(&socket, &state, &room).await;
+            return;
+        }
+
+        drop(state_guard);
+        sleep(Duration::from_secs(1)).await;
+    }
+}
+
+async fn reopen_lobby<'a>(socket: &SocketRef, state: &SharedAppState, state_guard: MutexGuard<'a, AppState>, room: &String) {
+    let _ = socket.within(room.clone()).emit("app_state_change", GameState::Lobby);
+    let _ = socket.within(room.clone()).emit("user_connect", get_game_data(room, &state_guard).await);
+    let settings = state_guard.games.get_settings(room).await;
+    drop(state_guard);
+
+    spawn_lobby_timer(socket, state, room, &settings);
+    generate_room_text(socket, state, room).await;
+}
+
+fn spawn_lobby_timer(socket: &SocketRef, state: &SharedAppState, room: &str, settings: &GameSettings) {
+    if let Some(lobby_timer) = settings.lobby_timer.filter(|_| settings.public) {
+        tokio::spawn(run_lobby_timer(socket.clone(), state.clone(), room.to_string(), lobby_timer));
+    }
+}
+
+async fn prepare_public_room(socket: &SocketRef, state: &SharedAppState, room: &String, settings: &GameSettings) {
+    if settings.public {
+        spawn_lobby_timer(socket, state, room, settings);
+        generate_room_text(socket, state, room).await;
+    }
+}
+
+async fn start_race<'a>(socket: &SocketRef, state: &SharedAppState, state_guard: MutexGuard<'a, AppState>, room: &String) {
+    if state_guard.games.get_game_state(room).await != GameState::Lobby {
+        return;
+    }
+
+    let game_text = state_guard.games.get_game_text(room).await;
+    if game_text.is_empty() {
+        let _ = socket.within(room.clone()).emit("missing_game_text", false);
+        return;
+    }
+
+    state_guard.games.start_game_countdown(room).await;
+    drop(state_guard);
+
+    run_countdown(socket, state, room, game_text, GAME_COUNTDOWN).await;
+    run_time_limit(socket, state, room).await;
+}
+
+async fn run_countdown(socket: &SocketRef, state: &SharedAppState, room: &String, game_text: String, mut seconds: u64) {
+    let _ = socket.within(room.clone()).emit("app_state_change", GameState::GameCountdown);
+    let _ = socket.within(room.clone()).emit("countdown_change", seconds);
+
+    let mut last_time = SystemTime::now().sub(Duration::from_secs(1));
+    while seconds > 0 {
+        let now = SystemTime::now();
+        sleep(now.duration_since(last_time).unwrap()).await;
+
+        seconds -= 1;
+        let _ = socket.within(room.clone()).emit("countdown_change", seconds);
+        last_time = now;
+    }
+
+    let _ = socket.within(room.clone()).emit("app_state_change", GameState::Game);
+    let _ = socket.within(room.clone()).emit("start_game", game_text);
+
+    let state_guard = state.lock().await;
+    state_guard.games.start_game(room).await;
+    let _ = socket.within(room.clone()).emit("text_metadata", state_guard.games.get_sources(room).await);
+
+    let passage_id = state_guard.games.get_passage_id(room).await;
+    if !passage_id.is_empty() {
+        let players = state_guard.games.get_player_names(room).await;
+        state_guard.passages.mark_played(&players, &passage_id).await;
+    }
+}
+
+async fn play_ghost(socket: SocketRef, state: SharedAppState, room: String, ghost_id: String, keystrokes: Vec<Keystroke>) {
+    let started_at = SystemTime::now();
+
+    for keystroke in keystrokes {
+        let elapsed = SystemTime::now().duration_since(started_at).unwrap_or_default();
+        sleep(Duration::from_millis(keystroke.time).saturating_sub(elapsed)).await;
+
+        let state_guard = state.lock().await;
+        if !state_guard.games.set_correct_len(&room, &ghost_id, keystroke.text_index).await {
+            return;
+        }
+
+        let user_text_change = UserTextChangeOut { user_id: ghost_id.clone(), text_index: keystroke.text_index };
+        let _ = socket.within(room.clone()).emit("character_change", user_text_change);
+
+        if state_guard.games.check_ending(&room, &ghost_id).await {
+            finish_game(&socket, &room, &state_guard).await;
+            return;
+        }
+    }
+}
+
+async fn play_replay(socket: SocketRef, replay_id: String, replay: Replay, speed: f64) {
+    let replay_start = ReplayStartOut {
+        replay_id,
+        text: replay.text,
+        user_map: replay.participants.iter().map(|(user_id, participant)| (user_id.clone(), participant.name.clone())).collect(),
+        color: replay.participants.iter().map(|(user_id, participant)| (user_id.clone(), participant.color)).collect()
+    };
+
+    if socket.emit("replay_start", replay_start).is_err() {
+        return;
+    }
+
+    let mut keystrokes: Vec<(String, Keystroke)> = replay.participants.into_iter()
+        .flat_map(|(user_id, participant)| participant.keystrokes.into_iter().map(move |keystroke| (user_id.clone(), keystroke)))
+        .collect();
+    keystrokes.sort_by_key(|(_, keystroke)| keystroke.time);
+
+    let started_at = SystemTime::now();
+    for (user_id, keystroke) in keystrokes {
+        let elapsed = SystemTime::now().duration_since(started_at).unwrap_or_default();
+        let time = Duration::from_millis(keystroke.time).div_f64(speed);
+        sleep(time.saturating_sub(elapsed)).await;
+
+        let user_text_change = UserTextChangeOut { user_id, text_index: keystroke.text_index };
+        if socket.emit("character_change", user_text_change).is_err() {
+            return;
+        }
+    }
+
+    let _ = socket.emit("replay_end", "");
+}
+
+async fn extend_game_text(socket: SocketRef, state: SharedAppState, room: String, settings: GameSettings, players: Vec<String>) {
+    let passage = take_or_generate_text(&state, settings.language, settings.text_length, &players).await;
+    let text = " ".to_string() + &passage.text;
+
+    let state_guard = state.lock().await;
+    if state_guard.games.append_game_text(&room, &text, passage.metadata()).await {
+        let _ = socket.within(room.clone()).emit("append_game_text", text);
+        let _ = socket.within(room.clone()).emit("text_metadata", state_guard.games.get_sources(&room).await);
+    }
+}
+
 async fn get_game_data<'a>(room: &String, state: &MutexGuard<'a, AppState>) -> UserConnectData {
     UserConnectData {
         user_map: state.games.get_all_users(room).await,
+        spectator_map: state.games.get_all_spectators(room).await,
+        late_users: state.games.get_late_users(room).await,
         correct_text_length_map: state.games.get_correct_len_all(room).await,
         app_state: state.games.get_game_state(room).await,
         color: state.games.get_all_user_color(room).await,
-        finished_generating_text: state.games.finished_generating_text(room).await
+        finished_generating_text: state.games.finished_generating_text(room).await,
+        host: state.games.get_host(room).await,
+        ready_map: state.games.get_ready_all(room).await,
+        chat_history: state.games.get_chat_history(room).await,
+        muted_users: state.games.get_muted(room).await,
+        passage_id: state.games.get_passage_id(room).await,
+        text_quality: state.games.get_quality(room).await,
+        settings: state.games.get_settings(room).await,
+        standings: state.games.get_standings(room).await,
+        team_map: state.games.get_all_user_teams(room).await
     }
 }
 
@@ -95,157 +483,378 @@ pub async fn handle_websocket_connection(socket: SocketRef) {
             return;
         }
         
-        let state = state.lock().await;
-        if !state.games.is_available(&user.room).await {
-            let _ = socket.emit("game_unavailable", "");
+        let state_guard = state.lock().await;
+        if apply_late_join_policy(&socket, &user, &state_guard).await {
             return;
         }
 
+        match state_guard.games.is_available(&user.room).await {
+            Availability::Available => {}
+            Availability::Full => {
+                let _ = socket.emit("game_full", "");
+                return;
+            }
+            Availability::Unavailable => {
+                let _ = socket.emit("game_unavailable", "");
+                return;
+            }
+        }
+
         info!("User: {} joined the room: {}", user.name, user.room);
-        user_join(&socket, &user, &state).await;
+        user_join(&socket, &user, &state_guard).await;
         
         let _ = socket.emit("allowed_to_join", "");
+        drop(state_guard);
+
+        check_auto_start(&socket, &state, &user.room).await;
     });
 
-    socket.on("create_game", |socket: SocketRef, Data::<UserInfo>(mut user), state: State<SharedAppState>| async move {
+    socket.on("spectate_game", |socket: SocketRef, Data::<UserInfo>(user), state: State<SharedAppState>| async move {
         if user.name.is_empty() {
             return;
         }
+
+        let state_guard = state.lock().await;
+        if !state_guard.games.is_spectatable(&user.room).await {
+            let _ = socket.emit("game_unavailable", "");
+            return;
+        }
+
+        let left_room = user_leave(&socket, &state_guard).await;
+
+        if !state_guard.games.contains_game(&user.room).await {
+            let _ = socket.emit("game_unavailable", "");
+            return;
+        }
+
+        info!("User: {} spectates the room: {}", user.name, user.room);
+        spectator_join(&socket, &user, &state_guard).await;
+        drop(state_guard);
+
+        let _ = socket.emit("allowed_to_spectate", "");
+
+        if let Some(room) = left_room.filter(|room| *room != user.room) {
+            check_auto_start(&socket, &state, &room).await;
+        }
+    });
+
+    socket.on("create_game", |socket: SocketRef, Data::<CreateGameInfo>(info), state: State<SharedAppState>| async move {
+        if info.name.is_empty() {
+            return;
+        }
         
-        let state = state.lock().await;
-        if state.users.contains_user(&socket.id.to_string()).await {
+        let state_guard = state.lock().await;
+        if state_guard.users.contains_user(&socket.id.to_string()).await {
             return;
         }
         
         let game_id = Uuid::new_v4().to_string();
-        create_game(&socket, &state, game_id, &mut user).await;
+        let mut user = UserInfo { name: info.name, room: String::new(), color: info.color };
+        create_game(&socket, &state_guard, game_id, &mut user, info.settings).await;
+        let settings = state_guard.games.get_settings(&user.room).await;
+        drop(state_guard);
+
+        prepare_public_room(&socket, &state, &user.room, &settings).await;
     });
     
+    socket.on("start_practice", |socket: SocketRef, Data::<CreateGameInfo>(info), state: State<SharedAppState>| async move {
+        if info.name.is_empty() {
+            return;
+        }
+
+        let state_guard = state.lock().await;
+        if state_guard.users.contains_user(&socket.id.to_string()).await {
+            return;
+        }
+
+        let game_id = Uuid::new_v4().to_string();
+        let mut user = UserInfo { name: info.name, room: String::new(), color: info.color };
+        let settings = GameSettings { practice: true, ..info.settings };
+        create_game(&socket, &state_guard, game_id, &mut user, settings.clone()).await;
+
+        state_guard.games.set_start_generating_text(&user.room).await;
+        drop(state_guard);
+
+        let passage = next_passage(&state, &settings, &[user.name.clone()]).await;
+        let game_text = passage.text.clone();
+
+        let state_guard = state.lock().await;
+        if !state_guard.games.contains_game(&user.room).await {
+            return;
+        }
+
+        state_guard.games.set_game_passage(&user.room, passage).await;
+        state_guard.games.start_game_countdown(&user.room).await;
+        drop(state_guard);
+
+        run_countdown(&socket, &state, &user.room, game_text, PRACTICE_COUNTDOWN).await;
+        run_time_limit(&socket, &state, &user.room).await;
+    });
+
+    socket.on("start_ghost_race", |socket: SocketRef, Data::<GhostRaceInfo>(info), state: State<SharedAppState>| async move {
+        if info.name.is_empty() {
+            return;
+        }
+
+        let state_guard = state.lock().await;
+        if state_guard.users.contains_user(&socket.id.to_string()).await {
+            return;
+        }
+
+        let Some(replay) = state_guard.replays.get_replay(&info.replay_id).await else {
+            let _ = socket.emit("replay_unavailable", "");
+            return;
+        };
+
+        let Some(participant) = replay.participants.get(&info.user_id).cloned() else {
+            let _ = socket.emit("replay_unavailable", "");
+            return;
+        };
+
+        let game_id = Uuid::new_v4().to_string();
+        let mut user = UserInfo { name: info.name, room: String::new(), color: None };
+        let settings = GameSettings { practice: true, ..GameSettings::default() };
+        create_game(&socket, &state_guard, game_id, &mut user, settings).await;
+
+        let ghost_id = format!("ghost:{}", info.user_id);
+        state_guard.games.add_ghost(ghost_id.clone(), participant.name, &user.room).await;
+        state_guard.games.set_game_text(&user.room, replay.text.clone()).await;
+        state_guard.games.set_sources(&user.room, replay.sources).await;
+        state_guard.games.start_game_countdown(&user.room).await;
+
+        let _ = socket.within(user.room.clone()).emit("user_connect", get_game_data(&user.room, &state_guard).await);
+        drop(state_guard);
+
+        run_countdown(&socket, &state, &user.room, replay.text, PRACTICE_COUNTDOWN).await;
+        tokio::spawn(play_ghost(socket.clone(), state.clone(), user.room.clone(), ghost_id, participant.keystrokes));
+        run_time_limit(&socket, &state, &user.room).await;
+    });
+
+    socket.on("watch_replay", |socket: SocketRef, Data::<WatchReplayInfo>(info), state: State<SharedAppState>| async move {
+        let state = state.lock().await;
+
+        let Some(replay) = state.replays.get_replay(&info.replay_id).await else {
+            let _ = socket.emit("replay_unavailable", "");
+            return;
+        };
+
+        let speed = info.speed.clamp(MIN_REPLAY_SPEED, MAX_REPLAY_SPEED);
+        tokio::spawn(play_replay(socket.clone(), info.replay_id, replay, speed));
+    });
+
     socket.on("play_again", |socket: SocketRef, Data::<UserInfo>(mut user), state: State<SharedAppState>| async move {
         info!("Received play_again");
-        let state = state.lock().await;
+        let state_guard = state.lock().await;
         let room = user.room.clone();
         
-        if state.games.get_game_state(&room).await != GameState::Ending {
+        if state_guard.games.get_game_state(&room).await != GameState::Ending {
             return;
         }
         
-        let mut game_id = state.games.get_followup_game_id(&room).await;
+        let mut game_id = state_guard.games.get_followup_game_id(&room).await;
+        let settings = state_guard.games.get_settings(&room).await;
         
-        if state.games.is_available(&game_id).await {
+        if state_guard.games.is_available(&game_id).await == Availability::Available {
             let _ = socket.emit("game_id", game_id.clone());
             user.room = game_id;
 
-            user_leave(&socket, &state).await;
-            user_join(&socket, &user, &state).await;
+            user_leave(&socket, &state_guard).await;
+            if apply_late_join_policy(&socket, &user, &state_guard).await {
+                return;
+            }
+
+            user_join(&socket, &user, &state_guard).await;
+            drop(state_guard);
+
+            check_auto_start(&socket, &state, &user.room).await;
         } else {
             game_id = Uuid::new_v4().to_string();
-            state.games.set_followup_game_id(&room, game_id.clone()).await;
+            state_guard.games.set_followup_game_id(&room, game_id.clone()).await;
 
-            user_leave(&socket, &state).await;
-            create_game(&socket, &state, game_id, &mut user).await;
+            user_leave(&socket, &state_guard).await;
+            create_game(&socket, &state_guard, game_id, &mut user, settings.clone()).await;
+            drop(state_guard);
+
+            prepare_public_room(&socket, &state, &user.room, &settings).await;
         }
     });
     
     socket.on("generate_game_text", |socket: SocketRef, Data::<UserInfo>(user), state: State<SharedAppState>| async move {
+        generate_room_text(&socket, &state, &user.room).await;
+    });
+
+    socket.on("use_passage", |socket: SocketRef, Data::<String>(passage_id), state: State<SharedAppState>| async move {
+        let state = state.lock().await;
+        let user_id = socket.id.to_string();
+        let Some(user) = state.users.get_user(&user_id).await else {
+            return;
+        };
+
+        if !state.games.is_host(&user.room, &user_id).await || state.games.get_game_state(&user.room).await != GameState::Lobby {
+            return;
+        }
+
+        let Some(passage) = state.passages.get_passage(&passage_id).await else {
+            let _ = socket.emit("passage_unavailable", passage_id);
+            return;
+        };
+
+        state.games.set_start_generating_text(&user.room).await;
+        state.games.set_game_passage(&user.room, passage).await;
+        let _ = socket.within(user.room.clone()).emit("created_game_text", true);
+        let _ = socket.within(user.room.clone()).emit("text_quality", state.games.get_quality(&user.room).await);
+    });
+
+    socket.on("set_custom_text", |socket: SocketRef, Data::<Vec<String>>(texts), state: State<SharedAppState>| async move {
+        let state = state.lock().await;
+        let user_id = socket.id.to_string();
+        let Some(user) = state.users.get_user(&user_id).await else {
+            return;
+        };
+
+        if !state.games.is_host(&user.room, &user_id).await || state.games.get_game_state(&user.room).await != GameState::Lobby {
+            return;
+        }
+
+        let texts = match clean_custom_texts(&texts) {
+            Ok(texts) => texts,
+            Err(error) => {
+                let _ = socket.emit("custom_text_rejected", error);
+                return;
+            }
+        };
+
+        let mut passage_ids = Vec::with_capacity(texts.len());
+        for (i, text) in texts.into_iter().enumerate() {
+            let title = format!("Custom text {}", i + 1);
+            let passage = state.passages.add_passage(TextSource::Custom, title, Language::default(), None, text).await;
+            passage_ids.push(passage.id);
+        }
+
+        state.games.set_custom_passages(&user.room, passage_ids).await;
+        let passage_id = state.games.take_custom_passage(&user.room).await.unwrap();
+        let passage = state.passages.get_passage(&passage_id).await.unwrap();
+
+        state.games.set_start_generating_text(&user.room).await;
+        state.games.set_game_passage(&user.room, passage).await;
+        let _ = socket.within(user.room.clone()).emit("created_game_text", true);
+        let _ = socket.within(user.room.clone()).emit("text_quality", state.games.get_quality(&user.room).await);
+    });
+
+    socket.on("next_round", |socket: SocketRef, state: State<SharedAppState>| async move {
         let state_guard = state.lock().await;
-        let room = user.room;
+        let user_id = socket.id.to_string();
+        let Some(user) = state_guard.users.get_user(&user_id).await else {
+            return;
+        };
 
-        if !state_guard.games.started_generating_text(&room).await {
-            state_guard.games.set_start_generating_text(&room).await;
-            drop(state_guard);
-            
-            let mut extract = String::new();
-            while extract.chars().count() < TEXT_SIZE { 
-                
-                let wikipedia_response = get_random_article_extract().await.unwrap();
-                let mut length = wikipedia_response.value.chars().count();
-
-                for i in TEXT_SIZE..wikipedia_response.value.chars().count() {
-                    if wikipedia_response.value.chars().nth(i).unwrap() == '.' {
-                        length = i + 1;
-                        break;
-                    }
-                }
-
-                if let Some(pretty_extract) = get_pretty_extract(wikipedia_response.value[..length].to_string()) {
-                    extract = pretty_extract;
-                }
+        if !state_guard.games.is_host(&user.room, &user_id).await || !state_guard.games.start_next_round(&user.room).await {
+            return;
+        }
+
+        info!("The room {} starts the next round", user.room);
+        reopen_lobby(&socket, &state, state_guard, &user.room).await;
+    });
+
+    socket.on("rematch", |socket: SocketRef, state: State<SharedAppState>| async move {
+        let state_guard = state.lock().await;
+        let user_id = socket.id.to_string();
+        let Some(user) = state_guard.users.get_user(&user_id).await else {
+            return;
+        };
+
+        if !state_guard.games.is_host(&user.room, &user_id).await {
+            let Some(votes) = state_guard.games.vote_rematch(&user.room, &user_id).await else {
+                return;
+            };
+
+            let _ = socket.within(user.room.clone()).emit("rematch_votes", votes);
+
+            if !state_guard.games.rematch_agreed(&user.room).await {
+                return;
             }
+        }
 
-            let state_guard = state.lock().await;
-            state_guard.games.set_game_text(&room, extract).await;
+        if !state_guard.games.rematch(&user.room).await {
+            return;
         }
 
-        let _ = socket.within(room).emit("created_game_text", true);
+        info!("The room {} starts a rematch", user.room);
+        reopen_lobby(&socket, &state, state_guard, &user.room).await;
     });
 
     socket.on_disconnect(|socket: SocketRef, state: State<SharedAppState>| async move {
         info!("Received on Disconnect");
-        let state = state.lock().await;
-        user_leave(&socket, &state).await;
+        let state_guard = state.lock().await;
+        let left_room = user_leave(&socket, &state_guard).await;
+        drop(state_guard);
+
+        if let Some(room) = left_room {
+            check_auto_start(&socket, &state, &room).await;
+        }
     });
     
     socket.on("leave_game", |socket: SocketRef, state: State<SharedAppState>| async move {
         info!("Received leave_game");
-        let state = state.lock().await;
-        user_leave(&socket, &state).await;
+        let state_guard = state.lock().await;
+        let left_room = user_leave(&socket, &state_guard).await;
+        drop(state_guard);
+
+        if let Some(room) = left_room {
+            check_auto_start(&socket, &state, &room).await;
+        }
     });
 
     socket.on("start_game", |socket: SocketRef, Data::<UserInfo>(user), state: State<SharedAppState>| async move {        
         info!("The game in the room {} was started!", user.room);
 
         let state_guard = state.lock().await;
-        
-        if state_guard.games.get_game_state(&user.room).await != GameState::Lobby {
-            return;
-        }
 
-        let game_text = state_guard.games.get_game_text(&user.room).await;
-        if game_text.is_empty() {
-            let _ = socket.within(user.room.clone()).emit("missing_game_text", false);
+        if !state_guard.games.is_host(&user.room, &socket.id.to_string()).await {
             return;
         }
 
-        state_guard.games.start_game_countdown(&user.room).await;
-        drop(state_guard);
-        
-        let _ = socket.within(user.room.clone()).emit("app_state_change", GameState::GameCountdown);
-
-        let mut seconds = 5;
-        let _ = socket.within(user.room.clone()).emit("countdown_change", seconds);
+        start_race(&socket, &state, state_guard, &user.room).await;
+    });
 
-        let mut last_time = SystemTime::now().sub(Duration::from_secs(1));
-        while seconds > 0 {
-            let now = SystemTime::now();
-            sleep(now.duration_since(last_time).unwrap()).await;
+    socket.on("toggle_ready", |socket: SocketRef, state: State<SharedAppState>| async move {
+        let state_guard = state.lock().await;
+        let user_id = socket.id.to_string();
+        let Some(user) = state_guard.users.get_user(&user_id).await else {
+            return;
+        };
 
-            seconds -= 1;
-            let _ = socket.within(user.room.clone()).emit("countdown_change", seconds);
-            last_time = now;
+        if !state_guard.games.toggle_ready(&user.room, &user_id).await {
+            return;
         }
 
-        let _ = socket.within(user.room.clone()).emit("app_state_change", GameState::Game);
-        let _ = socket.within(user.room.clone()).emit("start_game", game_text);
-        
-        let state_guard = state.lock().await;
-        state_guard.games.start_game(&user.room).await;
+        let _ = socket.within(user.room.clone()).emit("ready_change", state_guard.games.get_ready_all(&user.room).await);
+        drop(state_guard);
+
+        check_auto_start(&socket, &state, &user.room).await;
     });
 
     socket.on("push_character", |socket: SocketRef, Data::<char>(character), state: State<SharedAppState>| async move {
-        let state = state.lock().await;
+        let state_guard = state.lock().await;
         let user_id = socket.id.to_string();
-        let user = state.users.get_user(&user_id).await.unwrap();
+        let user = state_guard.users.get_user(&user_id).await.unwrap();
 
-        if let Some(text_index) = state.games.push_character(&user.room, &user_id, character).await {
+        if let Some(text_index) = state_guard.games.push_character(&user.room, &user_id, character).await {
             let user_text_change = UserTextChangeOut { user_id: user_id.clone(), text_index };
             let _ = socket.within(user.room.clone()).broadcast().emit("character_change", user_text_change);
+            emit_team_progress(&socket, &user.room, &state_guard).await;
 
-            if state.games.check_ending(&user.room, &user_id).await {
-                info!("The Game {} has finished", &user.room);
-                let _ = socket.within(user.room.clone()).emit("app_state_change", state.games.get_game_state(&user.room).await);
+            if state_guard.games.check_ending(&user.room, &user_id).await {
+                finish_game(&socket, &user.room, &state_guard).await;
             }
         }
+
+        if state_guard.games.needs_more_text(&user.room).await {
+            let settings = state_guard.games.get_settings(&user.room).await;
+            let players = state_guard.games.get_player_names(&user.room).await;
+            drop(state_guard);
+            tokio::spawn(extend_game_text(socket.clone(), state.clone(), user.room, settings, players));
+        }
     });
 
     socket.on("pop_character", |socket: SocketRef, state: State<SharedAppState>| async move {
@@ -256,6 +865,69 @@ pub async fn handle_websocket_connection(socket: SocketRef) {
         if let Some(text_index) = state.games.pop_character(&user.room, &user_id).await {
             let user_text_change = UserTextChangeOut { user_id, text_index };
             let _ = socket.within(user.room.clone()).broadcast().emit("character_change", user_text_change);
+            emit_team_progress(&socket, &user.room, &state).await;
+        }
+    });
+
+    socket.on("change_color", |socket: SocketRef, Data::<UserColor>(color), state: State<SharedAppState>| async move {
+        let state = state.lock().await;
+        let user_id = socket.id.to_string();
+        let Some(user) = state.users.get_user(&user_id).await else {
+            return;
+        };
+
+        if state.games.change_color(&user.room, &user_id, color).await {
+            let _ = socket.within(user.room.clone()).emit("color_change", state.games.get_all_user_color(&user.room).await);
+        } else {
+            let _ = socket.emit("color_unavailable", color);
+        }
+    });
+
+    socket.on("chat_message", |socket: SocketRef, Data::<String>(message), state: State<SharedAppState>| async move {
+        let state = state.lock().await;
+        let user_id = socket.id.to_string();
+        let Some(user) = state.users.get_user(&user_id).await else {
+            return;
+        };
+
+        match state.games.add_chat_message(&user.room, &user_id, message).await {
+            Ok(chat_message) => {
+                let _ = socket.within(user.room.clone()).emit("chat_message", chat_message);
+            }
+            Err(error) => {
+                let _ = socket.emit("chat_rejected", error);
+            }
+        }
+    });
+
+    socket.on("mute_player", |socket: SocketRef, Data::<MuteInfo>(info), state: State<SharedAppState>| async move {
+        let state = state.lock().await;
+        let user_id = socket.id.to_string();
+        let Some(user) = state.users.get_user(&user_id).await else {
+            return;
+        };
+
+        if !state.games.is_host(&user.room, &user_id).await || info.user_id == user_id {
+            return;
+        }
+
+        state.games.set_muted(&user.room, &info.user_id, info.muted).await;
+        let _ = socket.within(user.room.clone()).emit("muted_users", state.games.get_muted(&user.room).await);
+    });
+
+    socket.on("assign_team", |socket: SocketRef, Data::<TeamAssignment>(assignment), state: State<SharedAppState>| async move {
+        let state = state.lock().await;
+        let user_id = socket.id.to_string();
+        let Some(user) = state.users.get_user(&user_id).await else {
+            return;
+        };
+
+        if !state.games.is_host(&user.room, &user_id).await {
+            return;
+        }
+
+        if state.games.assign_team(&user.room, &assignment.user_id, assignment.team).await {
+            let _ = socket.within(user.room.clone()).emit("team_change", state.games.get_all_user_teams(&user.room).await);
         }
     });
 
@@ -263,10 +935,13 @@ pub async fn handle_websocket_connection(socket: SocketRef) {
         let state = state.lock().await;
         
         match state.games.is_available(&room).await {
-            true => {
+            Availability::Available => {
                 let _ = socket.emit("game_available", true);
             }
-            false => {
+            Availability::Full => {
+                let _ = socket.emit("game_full", false);
+            }
+            Availability::Unavailable => {
                 let _ = socket.emit("game_unavailable", false);
             }
         }
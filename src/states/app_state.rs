@@ -1,12 +1,22 @@
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use crate::states::game_state::GameStore;
+use crate::states::replay_state::ReplayStore;
 use crate::states::user_state::UserStore;
+use crate::text::cache::PassageCache;
+use crate::text::code::SnippetStore;
+use crate::text::pool::TextPool;
+use crate::text::wikipedia::WikipediaClient;
 
 #[derive(Default)]
 pub struct AppState {
     pub games: GameStore,
-    pub users: UserStore
+    pub users: UserStore,
+    pub replays: ReplayStore,
+    pub texts: TextPool,
+    pub passages: PassageCache,
+    pub wikipedia: WikipediaClient,
+    pub snippets: SnippetStore
 }
 
 pub type SharedAppState = Arc<Mutex<AppState>>;
@@ -1,23 +1,177 @@
-use std::cmp::min;
-use std::collections::HashMap;
+use std::cmp::{min, Reverse};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, SystemTime};
 
+use chrono::{DateTime, Utc};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
-use serde::Serialize;
-use strum::IntoEnumIterator;
+use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
-use crate::util::user_color::UserColor;
+use crate::states::replay_state::{Keystroke, Replay, ReplayParticipant};
+use crate::text::cache::{Passage, TextMetadata};
+use crate::text::code::INDENT_WIDTH;
+use crate::text::pool::TextLength;
+use crate::text::quality::{rate_text, TextQuality};
+use crate::text::wikipedia::Language;
+use crate::util::user_color::{Palette, UserColor};
 
 #[derive(Serialize, Debug, Clone)]
 pub struct Game {
     pub text: String,
+    pub passage_id: String,
+    pub sources: Vec<TextMetadata>,
+    pub quality: Option<TextQuality>,
+    pub custom_passages: VecDeque<String>,
     pub started_generating_text: bool,
     pub finished_generating_text: bool,
     pub users: HashMap<String, User>,
+    pub spectators: HashMap<String, String>,
     pub game_state: GameState,
     pub available_colors: Vec<UserColor>,
-    pub followup_game_id: String
+    pub followup_game_id: String,
+    pub settings: GameSettings,
+    pub started_at: Option<SystemTime>,
+    pub deadline: Option<SystemTime>,
+    pub extending_text: bool,
+    pub lobby_deadline: Option<SystemTime>,
+    pub host: String,
+    pub chat_history: VecDeque<ChatMessage>,
+    pub chat_rate: HashMap<String, VecDeque<SystemTime>>,
+    pub muted: HashSet<String>,
+    pub round: u32,
+    pub points: HashMap<String, u32>,
+    pub rematch_votes: HashSet<String>,
+    pub text_generation: u64
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SeriesStandings {
+    pub round: u32,
+    pub rounds: u32,
+    pub points: HashMap<String, u32>,
+    pub finished: bool
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ChatMessage {
+    pub user_id: String,
+    pub name: String,
+    pub message: String,
+    pub sent_at: DateTime<Utc>
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum ChatError {
+    Empty,
+    TooLong,
+    RateLimited,
+    Muted
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GameSettings {
+    #[serde(default)]
+    pub time_limit: Option<u64>,
+    #[serde(default)]
+    pub mode: GameMode,
+    #[serde(default)]
+    pub late_join: LateJoinPolicy,
+    #[serde(default = "default_max_players")]
+    pub max_players: usize,
+    #[serde(default)]
+    pub palette: Palette,
+    #[serde(default)]
+    pub auto_start_when_ready: bool,
+    #[serde(default = "default_rounds")]
+    pub rounds: u32,
+    #[serde(default)]
+    pub teams: Option<usize>,
+    #[serde(default)]
+    pub team_scoring: TeamScoring,
+    #[serde(default)]
+    pub public: bool,
+    #[serde(default)]
+    pub auto_start_players: Option<usize>,
+    #[serde(default)]
+    pub lobby_timer: Option<u64>,
+    #[serde(default)]
+    pub language: Language,
+    #[serde(default)]
+    pub code_language: Option<String>,
+    #[serde(default)]
+    pub text_length: TextLength,
+    #[serde(skip)]
+    pub practice: bool
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        GameSettings {
+            time_limit: None,
+            mode: GameMode::default(),
+            late_join: LateJoinPolicy::default(),
+            max_players: default_max_players(),
+            palette: Palette::default(),
+            auto_start_when_ready: false,
+            rounds: default_rounds(),
+            teams: None,
+            team_scoring: TeamScoring::default(),
+            public: false,
+            auto_start_players: None,
+            lobby_timer: None,
+            language: Language::default(),
+            code_language: None,
+            text_length: TextLength::default(),
+            practice: false
+        }
+    }
+}
+
+impl GameSettings {
+    fn lobby_deadline(&self) -> Option<SystemTime> {
+        self.lobby_timer
+            .filter(|_| self.public)
+            .map(|seconds| SystemTime::now() + Duration::from_secs(seconds))
+    }
+}
+
+fn default_max_players() -> usize {
+    DEFAULT_MAX_PLAYERS
+}
+
+fn default_rounds() -> u32 {
+    1
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Availability {
+    Available,
+    Full,
+    Unavailable
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum TeamScoring {
+    #[default]
+    Sum,
+    Average
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum LateJoinPolicy {
+    Block,
+    Spectate,
+    #[default]
+    Racer
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum GameMode {
+    #[default]
+    Passage,
+    Timed,
+    Code
 }
 
 #[derive(Serialize, Clone, Debug, PartialEq)]
@@ -33,12 +187,165 @@ pub struct User {
     name: String,
     text: String,
     correct_len: usize,
-    color: UserColor
+    color: UserColor,
+    keystrokes: usize,
+    mistakes: usize,
+    ghost: bool,
+    late: bool,
+    ready: bool,
+    team: Option<usize>,
+    skipped: usize,
+    timeline: Vec<Keystroke>
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Placement {
+    pub user_id: String,
+    pub place: Option<usize>,
+    pub correct_len: usize,
+    pub wpm: f64,
+    pub late: bool
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TeamPlacement {
+    pub team: usize,
+    pub place: usize,
+    pub members: Vec<String>,
+    pub score: f64
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UserStats {
+    pub user_id: String,
+    pub wpm: f64,
+    pub accuracy: f64,
+    pub correct_len: usize,
+    pub keystrokes: usize,
+    pub mistakes: usize,
+    pub seconds: f64
+}
+
+impl Game {
+    fn reset_race(&mut self) {
+        self.game_state = GameState::Lobby;
+        self.text.clear();
+        self.passage_id.clear();
+        self.sources.clear();
+        self.quality = None;
+        self.started_generating_text = false;
+        self.finished_generating_text = false;
+        self.started_at = None;
+        self.deadline = None;
+        self.extending_text = false;
+        self.lobby_deadline = self.settings.lobby_deadline();
+        self.rematch_votes.clear();
+        self.text_generation += 1;
+
+        let ghosts: Vec<String> = self.users.iter()
+            .filter(|(_, user)| user.ghost)
+            .map(|(user_id, _)| user_id.clone())
+            .collect();
+
+        for ghost_id in ghosts {
+            if let Some(ghost) = self.users.remove(&ghost_id) {
+                self.available_colors.push(ghost.color);
+            }
+        }
+
+        for user in self.users.values_mut() {
+            user.text.clear();
+            user.correct_len = 0;
+            user.keystrokes = 0;
+            user.mistakes = 0;
+            user.late = false;
+            user.ready = false;
+            user.skipped = 0;
+            user.timeline.clear();
+        }
+    }
+
+    fn apply_passage(&mut self, passage: Passage) {
+        self.finished_generating_text = true;
+        self.text_generation += 1;
+        self.sources = vec![passage.metadata()];
+        self.quality = Some(rate_text(&passage.text));
+        self.text = passage.text;
+        self.passage_id = passage.id;
+    }
+
+    fn standings(&self) -> SeriesStandings {
+        SeriesStandings {
+            round: self.round,
+            rounds: self.settings.rounds,
+            points: self.users.iter()
+                .filter(|(_, user)| !user.ghost)
+                .map(|(user_id, _)| (user_id.clone(), self.points.get(user_id).copied().unwrap_or_default()))
+                .collect(),
+            finished: self.round >= self.settings.rounds
+        }
+    }
+
+    fn smallest_team(&self) -> Option<usize> {
+        let teams = self.settings.teams?;
+        (0..teams).min_by_key(|team| self.users.values().filter(|user| user.team == Some(*team)).count())
+    }
+
+    fn team_scores(&self) -> Vec<(usize, Vec<String>, f64)> {
+        let Some(teams) = self.settings.teams else {
+            return Vec::new();
+        };
+
+        (0..teams)
+            .map(|team| {
+                let members: Vec<(&String, &User)> = self.users.iter()
+                    .filter(|(_, user)| user.team == Some(team))
+                    .collect();
+
+                let total: usize = members.iter().map(|(_, user)| user.correct_len).sum();
+                let score = match self.settings.team_scoring {
+                    TeamScoring::Sum => total as f64,
+                    TeamScoring::Average if members.is_empty() => 0.0,
+                    TeamScoring::Average => total as f64 / members.len() as f64
+                };
+
+                (team, members.into_iter().map(|(user_id, _)| user_id.clone()).collect(), score)
+            })
+            .collect()
+    }
+
+    fn take_color(&mut self, preferred: Option<UserColor>) -> UserColor {
+        if let Some(index) = preferred.and_then(|color| self.available_colors.iter().position(|available| *available == color)) {
+            return self.available_colors.remove(index);
+        }
+
+        self.available_colors.pop().unwrap()
+    }
+
+    fn elapsed(&self) -> Duration {
+        let now = SystemTime::now();
+        let end = self.deadline.map_or(now, |deadline| min(deadline, now));
+        self.started_at.and_then(|started_at| end.duration_since(started_at).ok()).unwrap_or_default()
+    }
+}
+
+fn words_per_minute(correct_len: usize, elapsed: Duration) -> f64 {
+    let minutes = elapsed.as_secs_f64() / 60.0;
+    if minutes > 0.0 { correct_len as f64 / 5.0 / minutes } else { 0.0 }
 }
 
 impl User {
     fn new(name: String, text: String, correct_len: usize, user_color: UserColor) -> Self {
-        User { name, text, correct_len, color: user_color }
+        User { name, text, correct_len, color: user_color, keystrokes: 0, mistakes: 0, ghost: false, late: false, ready: false, team: None, skipped: 0, timeline: Vec::new() }
+    }
+
+    fn record(&mut self, started_at: Option<SystemTime>) {
+        let time = started_at
+            .and_then(|started_at| SystemTime::now().duration_since(started_at).ok())
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        self.timeline.push(Keystroke { time, text_index: self.correct_len });
     }
 }
 
@@ -50,26 +357,72 @@ pub struct GameStore {
 }
 
 pub const TEXT_SIZE: usize = 250;
+pub const TIMED_DURATIONS: [u64; 4] = [15, 30, 60, 120];
+pub const DEFAULT_TIMED_DURATION: u64 = 60;
+pub const MIN_TIME_LIMIT: u64 = 5;
+pub const MAX_TIME_LIMIT: u64 = 3600;
+pub const MIN_LOBBY_TIMER: u64 = 5;
+pub const MAX_LOBBY_TIMER: u64 = 600;
+pub const EXTEND_TEXT_THRESHOLD: usize = 100;
+pub const DEFAULT_MAX_PLAYERS: usize = 10;
+pub const MAX_ROUNDS: u32 = 10;
+pub const MAX_TEAMS: usize = 4;
+pub const CHAT_MESSAGE_MAX_LEN: usize = 300;
+pub const CHAT_HISTORY_SIZE: usize = 50;
+pub const CHAT_RATE_LIMIT: usize = 5;
+pub const CHAT_RATE_WINDOW: Duration = Duration::from_secs(10);
 
 impl GameStore {
-    pub async fn init_game(&self, room: String) -> bool {
+    pub async fn init_game(&self, room: String, mut settings: GameSettings) -> bool {
         let mut binding = self.games.write().await;
 
         if binding.contains_key(&room) {
             return false;
         }
 
-        let mut available_colors: Vec<UserColor> = UserColor::iter().collect();
+        if settings.mode == GameMode::Timed && !TIMED_DURATIONS.contains(&settings.time_limit.unwrap_or_default()) {
+            settings.time_limit = Some(DEFAULT_TIMED_DURATION);
+        }
+
+        settings.time_limit = settings.time_limit.map(|seconds| seconds.clamp(MIN_TIME_LIMIT, MAX_TIME_LIMIT));
+
+        let mut available_colors = settings.palette.colors();
+        settings.max_players = settings.max_players.clamp(1, available_colors.len());
+        settings.rounds = settings.rounds.clamp(1, MAX_ROUNDS);
+        settings.teams = settings.teams.map(|teams| teams.clamp(2, MAX_TEAMS));
+        settings.lobby_timer = settings.lobby_timer.map(|seconds| seconds.clamp(MIN_LOBBY_TIMER, MAX_LOBBY_TIMER));
+        settings.auto_start_players = settings.auto_start_players.map(|players| players.clamp(1, settings.max_players));
+
         available_colors.shuffle(&mut thread_rng());
 
+        let lobby_deadline = settings.lobby_deadline();
+
         let game = Game {
             text: String::new(),
+            passage_id: String::new(),
+            sources: Vec::new(),
+            quality: None,
+            custom_passages: VecDeque::new(),
             started_generating_text: false,
             finished_generating_text: false,
             users: HashMap::new(),
+            spectators: HashMap::new(),
             game_state: GameState::Lobby,
             available_colors,
-            followup_game_id: String::new()
+            followup_game_id: String::new(),
+            settings,
+            started_at: None,
+            deadline: None,
+            extending_text: false,
+            lobby_deadline,
+            host: String::new(),
+            chat_history: VecDeque::new(),
+            chat_rate: HashMap::new(),
+            muted: HashSet::new(),
+            round: 1,
+            points: HashMap::new(),
+            rematch_votes: HashSet::new(),
+            text_generation: 0
         };
 
         binding.insert(room, game);
@@ -85,17 +438,129 @@ impl GameStore {
         let mut binding = self.games.write().await;
         let game = binding.get_mut(room).unwrap();
         game.finished_generating_text = true;
+        game.quality = Some(rate_text(&text));
         game.text = text;
     }
+
+    pub async fn set_game_passage(&self, room: &String, passage: Passage) {
+        let mut binding = self.games.write().await;
+        binding.get_mut(room).unwrap().apply_passage(passage);
+    }
+
+    pub async fn get_text_generation(&self, room: &String) -> u64 {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().text_generation
+    }
+
+    pub async fn set_generated_passage(&self, room: &String, passage: Passage, generation: u64) -> bool {
+        let mut binding = self.games.write().await;
+        let Some(game) = binding.get_mut(room) else {
+            return false;
+        };
+
+        if game.text_generation != generation {
+            return false;
+        }
+
+        game.apply_passage(passage);
+        true
+    }
+
+    pub async fn set_sources(&self, room: &String, sources: Vec<TextMetadata>) {
+        let mut binding = self.games.write().await;
+        binding.get_mut(room).unwrap().sources = sources;
+    }
+
+    pub async fn set_custom_passages(&self, room: &String, passage_ids: Vec<String>) {
+        let mut binding = self.games.write().await;
+        binding.get_mut(room).unwrap().custom_passages = passage_ids.into();
+    }
+
+    pub async fn take_custom_passage(&self, room: &String) -> Option<String> {
+        let mut binding = self.games.write().await;
+        binding.get_mut(room)?.custom_passages.pop_front()
+    }
+
+    pub async fn get_quality(&self, room: &String) -> Option<TextQuality> {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().quality
+    }
+
+    pub async fn get_sources(&self, room: &String) -> Vec<TextMetadata> {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().sources.clone()
+    }
+
+    pub async fn get_passage_id(&self, room: &String) -> String {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().passage_id.clone()
+    }
+
+    pub async fn needs_more_text(&self, room: &String) -> bool {
+        let mut binding = self.games.write().await;
+        let game = binding.get_mut(room).unwrap();
+
+        if game.settings.mode != GameMode::Timed || game.game_state != GameState::Game || game.extending_text {
+            return false;
+        }
+
+        let leader_len = game.users.values().map(|user| user.text.len()).max().unwrap_or_default();
+        if leader_len + EXTEND_TEXT_THRESHOLD < game.text.len() {
+            return false;
+        }
+
+        game.extending_text = true;
+        true
+    }
+
+    pub async fn append_game_text(&self, room: &String, text: &str, metadata: TextMetadata) -> bool {
+        let mut binding = self.games.write().await;
+
+        if let Some(game) = binding.get_mut(room) {
+            game.extending_text = false;
+
+            if game.game_state == GameState::Game {
+                game.text.push_str(text);
+                game.sources.push(metadata);
+                return true;
+            }
+        }
+
+        false
+    }
     
-    pub async fn is_available(&self, room: &String) -> bool {
+    pub async fn is_available(&self, room: &String) -> Availability {
+        let binding = self.games.read().await;
+        match binding.get(room) {
+            Some(game) if game.game_state == GameState::Ending || game.settings.practice => Availability::Unavailable,
+            Some(game) if game.users.len() >= game.settings.max_players => Availability::Full,
+            Some(_) => Availability::Available,
+            None => Availability::Unavailable
+        }
+    }
+
+    pub async fn is_spectatable(&self, room: &String) -> bool {
         let binding = self.games.read().await;
         if let Some(game) = binding.get(room) {
-            return game.game_state != GameState::Ending;
+            return !game.settings.practice;
         }
-        
+
+        false
+    }
+
+    pub async fn has_started(&self, room: &String) -> bool {
+        let binding = self.games.read().await;
+        if let Some(game) = binding.get(room) {
+            return matches!(game.game_state, GameState::GameCountdown | GameState::Game);
+        }
+
         false
     }
+
+    pub async fn contains_game(&self, room: &String) -> bool {
+        let binding = self.games.read().await;
+        binding.contains_key(room)
+    }
     
     pub async fn started_generating_text(&self, room: &String) -> bool {
         let binding = self.games.read().await;
@@ -115,40 +580,190 @@ impl GameStore {
         false
     }
 
-    pub async fn add_user(&self, user_id: String, user_name: String, room: &String) {
+    pub async fn add_user(&self, user_id: String, user_name: String, preferred_color: Option<UserColor>, room: &String) {
         let mut binding = self.games.write().await;
 
         if let Some(game) = binding.get_mut(room) {
-            if game.available_colors.is_empty() {
-                let mut available_colors: Vec<UserColor> = UserColor::iter().collect();
-                available_colors.shuffle(&mut thread_rng());
-                game.available_colors = available_colors;
+            let color = game.take_color(preferred_color);
+            let mut user = User::new(user_name, String::new(), 0, color);
+            user.late = game.game_state == GameState::Game;
+            user.team = game.smallest_team();
+            game.spectators.remove(&user_id);
+
+            if game.host.is_empty() {
+                game.host.clone_from(&user_id);
             }
-            
-            let user = User::new(user_name, String::new(), 0, game.available_colors.pop().unwrap());
+
             game.users.insert(user_id, user);
         } else {
             panic!()
         }
     }
 
+    pub async fn add_ghost(&self, ghost_id: String, ghost_name: String, room: &String) {
+        let mut binding = self.games.write().await;
+        let game = binding.get_mut(room).unwrap();
+
+        let color = game.take_color(None);
+        let mut ghost = User::new(ghost_name, String::new(), 0, color);
+        ghost.ghost = true;
+        game.users.insert(ghost_id, ghost);
+    }
+
+    pub async fn change_color(&self, room: &String, user_id: &String, color: UserColor) -> bool {
+        let mut binding = self.games.write().await;
+        let Some(game) = binding.get_mut(room) else {
+            return false;
+        };
+
+        if game.game_state != GameState::Lobby {
+            return false;
+        }
+
+        let Some(index) = game.available_colors.iter().position(|available| *available == color) else {
+            return false;
+        };
+
+        let Some(user) = game.users.get_mut(user_id) else {
+            return false;
+        };
+
+        game.available_colors.remove(index);
+        game.available_colors.push(user.color);
+        user.color = color;
+        true
+    }
+
+    pub async fn add_spectator(&self, user_id: String, user_name: String, room: &String) {
+        let mut binding = self.games.write().await;
+        binding.get_mut(room).unwrap().spectators.insert(user_id, user_name);
+    }
+
     pub async fn remove_user(&self, room: &String, user_id: &String) -> bool {
         let mut binding = self.games.write().await;
 
         if let Some(game) = binding.get_mut(room) {
             if let Some(user) = game.users.remove(user_id) {
                 game.available_colors.push(user.color);
-                
-                if game.users.is_empty() {
-                    binding.remove(room);
-                    return true;
-                }
+            } else if game.spectators.remove(user_id).is_none() {
+                return false;
             }
+
+            game.chat_rate.remove(user_id);
+            game.rematch_votes.remove(user_id);
+
+            if game.spectators.is_empty() && game.users.values().all(|user| user.ghost) {
+                binding.remove(room);
+                return true;
+            }
+
+            if game.host == *user_id {
+                game.host = game.users.iter()
+                    .find(|(_, user)| !user.ghost)
+                    .map(|(user_id, _)| user_id.clone())
+                    .unwrap_or_default();
+            }
+        }
+
+        false
+    }
+
+    pub async fn toggle_ready(&self, room: &String, user_id: &String) -> bool {
+        let mut binding = self.games.write().await;
+        let Some(game) = binding.get_mut(room) else {
+            return false;
+        };
+
+        if game.game_state != GameState::Lobby {
+            return false;
+        }
+
+        if let Some(user) = game.users.get_mut(user_id) {
+            user.ready = !user.ready;
+            return true;
         }
 
         false
     }
 
+    pub async fn get_ready_all(&self, room: &String) -> HashMap<String, bool> {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().users.iter().map(|(user_id, user)| (user_id.clone(), user.ready)).collect()
+    }
+
+    pub async fn is_host(&self, room: &String, user_id: &String) -> bool {
+        let binding = self.games.read().await;
+        binding.get(room).is_some_and(|game| game.host == *user_id)
+    }
+
+    pub async fn get_host(&self, room: &String) -> String {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().host.clone()
+    }
+
+    pub async fn add_chat_message(&self, room: &String, user_id: &String, message: String) -> Result<ChatMessage, ChatError> {
+        let mut binding = self.games.write().await;
+        let game = binding.get_mut(room).unwrap();
+
+        let message = message.trim().to_string();
+        if message.is_empty() {
+            return Err(ChatError::Empty);
+        }
+
+        if message.chars().count() > CHAT_MESSAGE_MAX_LEN {
+            return Err(ChatError::TooLong);
+        }
+
+        if game.muted.contains(user_id) {
+            return Err(ChatError::Muted);
+        }
+
+        let now = SystemTime::now();
+        let sent = game.chat_rate.entry(user_id.clone()).or_default();
+        sent.retain(|sent_at| now.duration_since(*sent_at).unwrap_or_default() < CHAT_RATE_WINDOW);
+
+        if sent.len() >= CHAT_RATE_LIMIT {
+            return Err(ChatError::RateLimited);
+        }
+
+        sent.push_back(now);
+
+        let name = match game.users.get(user_id) {
+            Some(user) => user.name.clone(),
+            None => game.spectators.get(user_id).cloned().unwrap_or_default()
+        };
+
+        let chat_message = ChatMessage { user_id: user_id.clone(), name, message, sent_at: Utc::now() };
+
+        if game.chat_history.len() >= CHAT_HISTORY_SIZE {
+            game.chat_history.pop_front();
+        }
+
+        game.chat_history.push_back(chat_message.clone());
+        Ok(chat_message)
+    }
+
+    pub async fn set_muted(&self, room: &String, user_id: &String, muted: bool) {
+        let mut binding = self.games.write().await;
+        let game = binding.get_mut(room).unwrap();
+
+        if muted {
+            game.muted.insert(user_id.clone());
+        } else {
+            game.muted.remove(user_id);
+        }
+    }
+
+    pub async fn get_muted(&self, room: &String) -> Vec<String> {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().muted.iter().cloned().collect()
+    }
+
+    pub async fn get_chat_history(&self, room: &String) -> VecDeque<ChatMessage> {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().chat_history.clone()
+    }
+
     pub async fn push_character(&self, room: &String, user_id: &String, character: char) -> Option<usize> {
         if !character.is_ascii() {
             return None;
@@ -156,13 +771,42 @@ impl GameStore {
 
         let mut binding = self.games.write().await;
         let game = binding.get_mut(room).unwrap();
-        let user = game.users.get_mut(user_id).unwrap();
+
+        if game.game_state != GameState::Game {
+            return None;
+        }
+
+        let user = game.users.get_mut(user_id)?;
+
+        if game.settings.mode == GameMode::Code && user.correct_len == user.text.len() {
+            let remaining = &game.text[user.text.len()..];
+
+            let skipped = match character {
+                '\t' if remaining.starts_with(' ') => remaining.bytes().take(INDENT_WIDTH).take_while(|byte| *byte == b' ').count(),
+                '\n' if remaining.starts_with('\n') => 1 + remaining[1..].bytes().take_while(|byte| *byte == b' ').count(),
+                _ => 0
+            };
+
+            if skipped > 0 {
+                user.skipped = skipped;
+                user.text.push_str(&remaining[..skipped]);
+                user.keystrokes += 1;
+                user.correct_len += skipped;
+                user.record(game.started_at);
+                return Some(user.correct_len);
+            }
+        }
 
         if user.text.len() < game.text.len() {
+            user.skipped = 0;
             user.text.push(character);
+            user.keystrokes += 1;
 
-            if user.correct_len == user.text.len() - 1 && character == game.text.chars().nth(user.text.len() - 1).unwrap() {
+            if character != game.text.chars().nth(user.text.len() - 1).unwrap() {
+                user.mistakes += 1;
+            } else if user.correct_len == user.text.len() - 1 {
                 user.correct_len += 1;
+                user.record(game.started_at);
                 return Some(user.correct_len);
             }
         }
@@ -172,18 +816,53 @@ impl GameStore {
 
     pub async fn pop_character(&self, room: &String, user_id: &String) -> Option<usize> {
         let mut binding = self.games.write().await;
-        let user = binding.get_mut(room).unwrap().users.get_mut(user_id).unwrap();
+        let game = binding.get_mut(room).unwrap();
+        let user = game.users.get_mut(user_id)?;
 
-        user.text.pop();
+        for _ in 0..std::mem::take(&mut user.skipped).max(1) {
+            user.text.pop();
+        }
 
         if user.correct_len > user.text.len() {
-            user.correct_len -= 1;
+            user.correct_len = user.text.len();
+            user.record(game.started_at);
             return Some(user.correct_len);
         }
 
         None
     }
     
+    pub async fn set_correct_len(&self, room: &String, user_id: &String, correct_len: usize) -> bool {
+        let mut binding = self.games.write().await;
+
+        if let Some(game) = binding.get_mut(room) {
+            if game.game_state == GameState::Game {
+                if let Some(user) = game.users.get_mut(user_id) {
+                    user.correct_len = correct_len;
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    pub async fn get_replay(&self, room: &String) -> Replay {
+        let binding = self.games.read().await;
+        let game = binding.get(room).unwrap();
+
+        let participants = game.users.iter()
+            .filter(|(_, user)| !user.ghost)
+            .map(|(user_id, user)| (user_id.clone(), ReplayParticipant {
+                name: user.name.clone(),
+                color: user.color,
+                keystrokes: user.timeline.clone()
+            }))
+            .collect();
+
+        Replay { recorded_at: Utc::now(), text: game.text.clone(), sources: game.sources.clone(), participants }
+    }
+
     pub async fn get_game_text(&self, room: &String) -> String {
         let binding = self.games.read().await;
         binding.get(room).unwrap().text.clone()
@@ -223,11 +902,101 @@ impl GameStore {
         binding.get(room).unwrap().users.iter().map(|(user_id, user)| (user_id.clone(), user.name.clone())).collect()
     }
 
+    pub async fn get_late_users(&self, room: &String) -> Vec<String> {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().users.iter().filter(|(_, user)| user.late).map(|(user_id, _)| user_id.clone()).collect()
+    }
+
+    pub async fn get_player_names(&self, room: &String) -> Vec<String> {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().users.values().filter(|user| !user.ghost).map(|user| user.name.clone()).collect()
+    }
+
+    pub async fn get_all_spectators(&self, room: &String) -> HashMap<String, String> {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().spectators.clone()
+    }
+
     pub async fn get_all_user_color(&self, room: &String) -> HashMap<String, UserColor> {
         let binding = self.games.read().await;
         binding.get(room).unwrap().users.iter().map(|(user_id, user)| (user_id.clone(), user.color)).collect()
     }
 
+    pub async fn get_all_user_teams(&self, room: &String) -> HashMap<String, usize> {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().users.iter()
+            .filter_map(|(user_id, user)| user.team.map(|team| (user_id.clone(), team)))
+            .collect()
+    }
+
+    pub async fn assign_team(&self, room: &String, user_id: &String, team: usize) -> bool {
+        let mut binding = self.games.write().await;
+        let Some(game) = binding.get_mut(room) else {
+            return false;
+        };
+
+        if game.game_state != GameState::Lobby || game.settings.teams.is_none_or(|teams| team >= teams) {
+            return false;
+        }
+
+        match game.users.get_mut(user_id) {
+            Some(user) if !user.ghost => {
+                user.team = Some(team);
+                true
+            }
+            _ => false
+        }
+    }
+
+    pub async fn get_team_progress(&self, room: &String) -> HashMap<usize, f64> {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().team_scores().into_iter()
+            .map(|(team, _, score)| (team, score))
+            .collect()
+    }
+
+    pub async fn get_team_placements(&self, room: &String) -> Vec<TeamPlacement> {
+        let binding = self.games.read().await;
+        let mut scores = binding.get(room).unwrap().team_scores();
+        scores.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+
+        scores.into_iter()
+            .enumerate()
+            .map(|(i, (team, members, score))| TeamPlacement { team, place: i + 1, members, score })
+            .collect()
+    }
+
+    pub async fn should_auto_start(&self, room: &String) -> bool {
+        let binding = self.games.read().await;
+        let Some(game) = binding.get(room) else {
+            return false;
+        };
+
+        let players = game.users.values().filter(|user| !user.ghost).count();
+
+        if game.game_state != GameState::Lobby || !game.finished_generating_text || players == 0 {
+            return false;
+        }
+
+        if game.settings.auto_start_when_ready && game.users.values().filter(|user| !user.ghost).all(|user| user.ready) {
+            return true;
+        }
+
+        if !game.settings.public {
+            return false;
+        }
+
+        let enough_players = game.settings.auto_start_players.is_some_and(|min_players| players >= min_players);
+        let lobby_elapsed = game.lobby_deadline.is_some_and(|deadline| SystemTime::now() >= deadline);
+
+        enough_players || lobby_elapsed
+    }
+
+    pub async fn find_game_state(&self, room: &String) -> Option<GameState> {
+        let binding = self.games.read().await;
+        binding.get(room).map(|game| game.game_state.clone())
+    }
+
     pub async fn get_game_state(&self, room: &String) -> GameState {
         let binding = self.games.read().await;
         binding.get(room).unwrap().game_state.clone()
@@ -235,7 +1004,152 @@ impl GameStore {
 
     pub async fn start_game(&self, room: &String) {
         let mut binding = self.games.write().await;
-        binding.get_mut(room).unwrap().game_state = GameState::Game;
+        let game = binding.get_mut(room).unwrap();
+        let now = SystemTime::now();
+        game.game_state = GameState::Game;
+        game.started_at = Some(now);
+        game.deadline = game.settings.time_limit.map(|seconds| now + Duration::from_secs(seconds));
+    }
+
+    pub async fn get_settings(&self, room: &String) -> GameSettings {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().settings.clone()
+    }
+
+    pub async fn get_time_remaining(&self, room: &String) -> Option<u64> {
+        let binding = self.games.read().await;
+        let game = binding.get(room)?;
+
+        if game.game_state != GameState::Game {
+            return None;
+        }
+
+        let remaining = game.deadline?.duration_since(SystemTime::now()).unwrap_or_default();
+        Some(remaining.as_millis().div_ceil(1000) as u64)
+    }
+
+    pub async fn end_game(&self, room: &String) {
+        let mut binding = self.games.write().await;
+        binding.get_mut(room).unwrap().game_state = GameState::Ending;
+    }
+
+    pub async fn get_placements(&self, room: &String) -> Vec<Placement> {
+        let binding = self.games.read().await;
+        let game = binding.get(room).unwrap();
+
+        let elapsed = game.elapsed();
+
+        let mut users: Vec<(&String, &User)> = game.users.iter().collect();
+        users.sort_by_key(|(_, user)| (user.late, Reverse(user.correct_len)));
+
+        users.into_iter()
+            .enumerate()
+            .map(|(i, (user_id, user))| Placement {
+                user_id: user_id.clone(),
+                place: (!user.late).then_some(i + 1),
+                correct_len: user.correct_len,
+                wpm: words_per_minute(user.correct_len, elapsed),
+                late: user.late
+            })
+            .collect()
+    }
+
+    pub async fn award_points(&self, room: &String) -> SeriesStandings {
+        let placements = self.get_placements(room).await;
+
+        let mut binding = self.games.write().await;
+        let game = binding.get_mut(room).unwrap();
+        let placed = placements.iter().filter(|placement| placement.place.is_some()).count();
+
+        for placement in placements {
+            if let Some(place) = placement.place {
+                *game.points.entry(placement.user_id).or_default() += (placed - place + 1) as u32;
+            }
+        }
+
+        game.standings()
+    }
+
+    pub async fn get_standings(&self, room: &String) -> SeriesStandings {
+        let binding = self.games.read().await;
+        binding.get(room).unwrap().standings()
+    }
+
+    pub async fn start_next_round(&self, room: &String) -> bool {
+        let mut binding = self.games.write().await;
+        let Some(game) = binding.get_mut(room) else {
+            return false;
+        };
+
+        if game.game_state != GameState::Ending || game.round >= game.settings.rounds {
+            return false;
+        }
+
+        game.round += 1;
+        game.reset_race();
+        true
+    }
+
+    pub async fn vote_rematch(&self, room: &String, user_id: &String) -> Option<Vec<String>> {
+        let mut binding = self.games.write().await;
+        let game = binding.get_mut(room)?;
+
+        if game.game_state != GameState::Ending || game.users.get(user_id).is_none_or(|user| user.ghost) {
+            return None;
+        }
+
+        game.rematch_votes.insert(user_id.clone());
+        Some(game.rematch_votes.iter().cloned().collect())
+    }
+
+    pub async fn rematch_agreed(&self, room: &String) -> bool {
+        let binding = self.games.read().await;
+        let Some(game) = binding.get(room) else {
+            return false;
+        };
+
+        let players = game.users.values().filter(|user| !user.ghost).count();
+        game.rematch_votes.len() * 2 > players
+    }
+
+    pub async fn rematch(&self, room: &String) -> bool {
+        let mut binding = self.games.write().await;
+        let Some(game) = binding.get_mut(room) else {
+            return false;
+        };
+
+        if game.game_state != GameState::Ending {
+            return false;
+        }
+
+        game.round = 1;
+        game.points.clear();
+        game.reset_race();
+        true
+    }
+
+    pub async fn get_user_stats(&self, room: &String) -> Vec<UserStats> {
+        let binding = self.games.read().await;
+        let game = binding.get(room).unwrap();
+        let elapsed = game.elapsed();
+
+        game.users.iter().filter(|(_, user)| !user.ghost).map(|(user_id, user)| {
+            let accuracy = if user.keystrokes > 0 {
+                (user.keystrokes - user.mistakes) as f64 / user.keystrokes as f64
+            } else {
+                0.0
+            };
+
+            UserStats {
+                user_id: user_id.clone(),
+                wpm: words_per_minute(user.correct_len, elapsed),
+                accuracy,
+                correct_len: user.correct_len,
+                keystrokes: user.keystrokes,
+                mistakes: user.mistakes,
+                seconds: elapsed.as_secs_f64()
+            }
+        }).collect()
     }
     
     pub async fn start_game_countdown(&self, room: &String) {
@@ -245,6 +1159,11 @@ impl GameStore {
 
     pub async fn check_ending(&self, room: &String, user_id: &String) -> bool {
         let mut binding = self.games.write().await;
+
+        if binding.get(room).unwrap().settings.mode == GameMode::Timed {
+            return false;
+        }
+
         let game_text_length = binding.get(room).unwrap().text.len();
         let user_correct_len = binding.get(room).unwrap().users.get(user_id).unwrap().correct_len;
 
@@ -265,4 +1184,81 @@ impl GameStore {
         let binding = self.games.read().await;
         binding.get(room).unwrap().followup_game_id.clone()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = "fn main() {\n    let x = 1;\n        x\n}";
+
+    async fn code_race(text: &str) -> (GameStore, String, String) {
+        let store = GameStore::default();
+        let room = "room".to_string();
+        let user_id = "user".to_string();
+
+        store.init_game(room.clone(), GameSettings { mode: GameMode::Code, ..GameSettings::default() }).await;
+        store.add_user(user_id.clone(), "Ada".to_string(), None, &room).await;
+        store.set_game_text(&room, text.to_string()).await;
+        store.start_game(&room).await;
+
+        (store, room, user_id)
+    }
+
+    async fn type_text(store: &GameStore, room: &String, user_id: &String, text: &str) -> Option<usize> {
+        let mut correct_len = None;
+        for character in text.chars() {
+            correct_len = store.push_character(room, user_id, character).await.or(correct_len);
+        }
+        correct_len
+    }
+
+    #[tokio::test]
+    async fn newline_skips_following_indentation() {
+        let (store, room, user_id) = code_race(CODE).await;
+
+        type_text(&store, &room, &user_id, "fn main() {").await;
+        assert_eq!(store.push_character(&room, &user_id, '\n').await, Some("fn main() {\n    ".len()));
+    }
+
+    #[tokio::test]
+    async fn newline_skips_deep_indentation() {
+        let (store, room, user_id) = code_race(CODE).await;
+
+        type_text(&store, &room, &user_id, "fn main() {\nlet x = 1;").await;
+        assert_eq!(store.push_character(&room, &user_id, '\n').await, Some("fn main() {\n    let x = 1;\n        ".len()));
+    }
+
+    #[tokio::test]
+    async fn tab_skips_at_most_one_indent() {
+        let (store, room, user_id) = code_race("        b").await;
+
+        assert_eq!(store.push_character(&room, &user_id, '\t').await, Some(INDENT_WIDTH));
+        assert_eq!(store.push_character(&room, &user_id, '\t').await, Some(INDENT_WIDTH * 2));
+        assert_eq!(store.push_character(&room, &user_id, '\t').await, None);
+    }
+
+    #[tokio::test]
+    async fn no_skip_with_uncorrected_mistakes() {
+        let (store, room, user_id) = code_race(CODE).await;
+
+        type_text(&store, &room, &user_id, "fn main() ").await;
+        assert_eq!(store.push_character(&room, &user_id, '\n').await, None);
+        assert_eq!(store.pop_character(&room, &user_id).await, None);
+        assert_eq!(store.push_character(&room, &user_id, '{').await, Some("fn main() {".len()));
+    }
+
+    #[tokio::test]
+    async fn backspace_undoes_a_skip() {
+        let (store, room, user_id) = code_race(CODE).await;
+
+        type_text(&store, &room, &user_id, "fn main() {").await;
+        store.push_character(&room, &user_id, '\n').await;
+
+        assert_eq!(store.pop_character(&room, &user_id).await, Some("fn main() {".len()));
+        assert_eq!(store.pop_character(&room, &user_id).await, Some("fn main() ".len()));
+        assert_eq!(store.push_character(&room, &user_id, '{').await, Some("fn main() {".len()));
+        assert_eq!(store.push_character(&room, &user_id, '\n').await, Some("fn main() {\n    ".len()));
+    }
+}
+
@@ -1,3 +1,4 @@
 pub mod app_state;
 pub mod game_state;
+pub mod replay_state;
 pub mod user_state;
\ No newline at end of file
@@ -0,0 +1,70 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::text::cache::TextMetadata;
+use crate::util::user_color::UserColor;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(into = "(u64, usize)")]
+pub struct Keystroke {
+    pub time: u64,
+    pub text_index: usize
+}
+
+impl From<Keystroke> for (u64, usize) {
+    fn from(keystroke: Keystroke) -> Self {
+        (keystroke.time, keystroke.text_index)
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ReplayParticipant {
+    pub name: String,
+    pub color: UserColor,
+    pub keystrokes: Vec<Keystroke>
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Replay {
+    pub recorded_at: DateTime<Utc>,
+    pub text: String,
+    pub sources: Vec<TextMetadata>,
+    pub participants: HashMap<String, ReplayParticipant>
+}
+
+pub type ReplayMap = HashMap<String, Replay>;
+
+pub const MAX_REPLAYS: usize = 1000;
+
+#[derive(Default)]
+pub struct ReplayStore {
+    pub replays: RwLock<ReplayMap>,
+    pub order: RwLock<VecDeque<String>>
+}
+
+impl ReplayStore {
+    pub async fn add_replay(&self, replay: Replay) -> String {
+        let replay_id = Uuid::new_v4().to_string();
+        let mut replays = self.replays.write().await;
+        let mut order = self.order.write().await;
+
+        if order.len() >= MAX_REPLAYS {
+            if let Some(oldest) = order.pop_front() {
+                replays.remove(&oldest);
+            }
+        }
+
+        replays.insert(replay_id.clone(), replay);
+        order.push_back(replay_id.clone());
+        replay_id
+    }
+
+    pub async fn get_replay(&self, replay_id: &String) -> Option<Replay> {
+        let binding = self.replays.read().await;
+        binding.get(replay_id).cloned()
+    }
+}
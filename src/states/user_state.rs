@@ -3,10 +3,14 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
+use crate::util::user_color::UserColor;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserInfo {
     pub name: String,
-    pub room: String
+    pub room: String,
+    #[serde(default)]
+    pub color: Option<UserColor>
 }
 
 pub type RoomStore = HashMap<String, UserInfo>;
@@ -0,0 +1,186 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::text::wikipedia::Language;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextSource {
+    Wikipedia,
+    Custom,
+    Code
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Passage {
+    pub id: String,
+    pub source: TextSource,
+    pub title: String,
+    pub language: Language,
+    pub code_language: Option<String>,
+    pub text: String
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TextMetadata {
+    pub passage_id: String,
+    pub source: TextSource,
+    pub title: String,
+    pub url: String,
+    pub language: Option<Language>,
+    pub code_language: Option<String>,
+    pub license: String,
+    pub license_url: String
+}
+
+impl Passage {
+    pub fn metadata(&self) -> TextMetadata {
+        match self.source {
+            TextSource::Wikipedia => {
+                let mut url = Url::parse(&format!("https://{}.wikipedia.org/wiki/", self.language.subdomain())).unwrap();
+                url.path_segments_mut().unwrap().pop_if_empty().push(&self.title.replace(' ', "_"));
+
+                TextMetadata {
+                    passage_id: self.id.clone(),
+                    source: self.source,
+                    title: self.title.clone(),
+                    url: url.to_string(),
+                    language: Some(self.language),
+                    code_language: None,
+                    license: format!("Text from the Wikipedia article \"{}\", available under CC BY-SA 4.0", self.title),
+                    license_url: WIKIPEDIA_LICENSE_URL.to_string()
+                }
+            }
+            TextSource::Custom => TextMetadata {
+                passage_id: self.id.clone(),
+                source: self.source,
+                title: self.title.clone(),
+                url: String::new(),
+                language: Some(self.language),
+                code_language: None,
+                license: "Provided by the room host".to_string(),
+                license_url: String::new()
+            },
+            TextSource::Code => TextMetadata {
+                passage_id: self.id.clone(),
+                source: self.source,
+                title: self.title.clone(),
+                url: String::new(),
+                language: None,
+                code_language: self.code_language.clone(),
+                license: format!("Source snippet \"{}\" from the server's snippet collection", self.title),
+                license_url: String::new()
+            }
+        }
+    }
+}
+
+pub type PassageMap = HashMap<String, Passage>;
+
+pub const MAX_PASSAGES: usize = 10000;
+pub const RECENT_PASSAGES: usize = 20;
+pub const WIKIPEDIA_LICENSE_URL: &str = "https://creativecommons.org/licenses/by-sa/4.0/";
+
+#[derive(Default)]
+pub struct PassageCache {
+    pub passages: RwLock<PassageMap>,
+    pub titles: RwLock<HashMap<(TextSource, Language, String), String>>,
+    pub order: RwLock<VecDeque<String>>,
+    pub recent: RwLock<HashMap<String, VecDeque<String>>>
+}
+
+impl PassageCache {
+    pub async fn add_passage(&self, source: TextSource, title: String, language: Language, code_language: Option<String>, text: String) -> Passage {
+        let mut passages = self.passages.write().await;
+        let mut titles = self.titles.write().await;
+        let mut order = self.order.write().await;
+
+        let key = (source, language, title.clone());
+
+        if source != TextSource::Custom {
+            if let Some(passage) = titles.get(&key).and_then(|passage_id| passages.get(passage_id)) {
+                if passage.text == text {
+                    return passage.clone();
+                }
+            }
+        }
+
+        if order.len() >= MAX_PASSAGES {
+            if let Some(oldest) = order.pop_front().and_then(|passage_id| passages.remove(&passage_id)) {
+                let oldest_key = (oldest.source, oldest.language, oldest.title);
+
+                if titles.get(&oldest_key) == Some(&oldest.id) {
+                    titles.remove(&oldest_key);
+                }
+            }
+        }
+
+        let passage = Passage { id: Uuid::new_v4().to_string(), source, title, language, code_language, text };
+        if source != TextSource::Custom {
+            titles.insert(key, passage.id.clone());
+        }
+
+        order.push_back(passage.id.clone());
+        passages.insert(passage.id.clone(), passage.clone());
+        passage
+    }
+
+    pub async fn get_passage(&self, passage_id: &String) -> Option<Passage> {
+        let binding = self.passages.read().await;
+        binding.get(passage_id).cloned()
+    }
+
+    pub async fn mark_played(&self, players: &[String], passage_id: &String) {
+        let mut binding = self.recent.write().await;
+
+        for player in players {
+            let recent = binding.entry(player.clone()).or_default();
+            recent.retain(|recent_id| recent_id != passage_id);
+
+            if recent.len() >= RECENT_PASSAGES {
+                recent.pop_front();
+            }
+
+            recent.push_back(passage_id.clone());
+        }
+    }
+
+    pub async fn recently_played(&self, players: &[String]) -> HashSet<String> {
+        let binding = self.recent.read().await;
+        players.iter().filter_map(|player| binding.get(player)).flatten().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn same_title_in_other_language_is_a_new_passage() {
+        let cache = PassageCache::default();
+
+        let english = cache.add_passage(TextSource::Wikipedia, "Moon".to_string(), Language::English, None, "The Moon is Earth's only natural satellite.".to_string()).await;
+        let simple = cache.add_passage(TextSource::Wikipedia, "Moon".to_string(), Language::SimpleEnglish, None, "The Moon is a big rock.".to_string()).await;
+
+        assert_ne!(english.id, simple.id);
+        assert_eq!(cache.get_passage(&english.id).await.unwrap().text, english.text);
+        assert_eq!(simple.metadata().url, "https://simple.wikipedia.org/wiki/Moon");
+    }
+
+    #[tokio::test]
+    async fn existing_passages_keep_their_text() {
+        let cache = PassageCache::default();
+
+        let short = cache.add_passage(TextSource::Wikipedia, "Moon".to_string(), Language::English, None, "Short text.".to_string()).await;
+        let repeated = cache.add_passage(TextSource::Wikipedia, "Moon".to_string(), Language::English, None, "Short text.".to_string()).await;
+        let long = cache.add_passage(TextSource::Wikipedia, "Moon".to_string(), Language::English, None, "A much longer text.".to_string()).await;
+
+        assert_eq!(short.id, repeated.id);
+        assert_ne!(short.id, long.id);
+        assert_eq!(cache.get_passage(&short.id).await.unwrap().text, "Short text.");
+        assert_eq!(cache.get_passage(&long.id).await.unwrap().text, "A much longer text.");
+    }
+}
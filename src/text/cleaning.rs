@@ -0,0 +1,269 @@
+use regex::Regex;
+
+pub trait TextFilter: Send + Sync {
+    fn apply(&self, text: String) -> Option<String>;
+}
+
+pub struct NormalizePunctuation;
+
+impl TextFilter for NormalizePunctuation {
+    fn apply(&self, text: String) -> Option<String> {
+        let text = text
+            .replace(['\u{2018}', '\u{2019}', '\u{201A}', '\u{2032}'], "'")
+            .replace(['\u{201C}', '\u{201D}', '\u{201E}', '\u{2033}'], "\"")
+            .replace(['\u{2010}', '\u{2011}', '\u{2012}', '\u{2013}', '\u{2014}', '\u{2212}'], "-")
+            .replace('\u{2026}', "...")
+            .replace('\u{00A0}', " ");
+
+        Some(text)
+    }
+}
+
+pub struct RejectNonAscii;
+
+impl TextFilter for RejectNonAscii {
+    fn apply(&self, text: String) -> Option<String> {
+        if text.is_ascii() { Some(text) } else { None }
+    }
+}
+
+pub struct RemoveHeadlines;
+
+impl TextFilter for RemoveHeadlines {
+    fn apply(&self, text: String) -> Option<String> {
+        let regex = Regex::new(r"(?m)^\s*==+[^=\n]*==+\s*$").unwrap();
+        Some(regex.replace_all(&text, "").to_string())
+    }
+}
+
+pub struct DropListLines;
+
+impl TextFilter for DropListLines {
+    fn apply(&self, text: String) -> Option<String> {
+        let list_item = Regex::new(r"^\s*([-*+#]|\d+[.)])\s").unwrap();
+
+        let lines: Vec<&str> = text
+            .lines()
+            .filter(|line| {
+                let line = line.trim();
+                line.is_empty() || (!list_item.is_match(line) && line.ends_with(['.', '!', '?', '"']))
+            })
+            .collect();
+
+        Some(lines.join("\n"))
+    }
+}
+
+pub struct RemoveParentheticals;
+
+impl TextFilter for RemoveParentheticals {
+    fn apply(&self, mut text: String) -> Option<String> {
+        let brackets = Regex::new(r"\s*(\([^()]*\)|\[[^\[\]]*\])").unwrap();
+
+        while brackets.is_match(&text) {
+            text = brackets.replace_all(&text, "").to_string();
+        }
+
+        let space_before_punctuation = Regex::new(r"\s+([,.;:!?])").unwrap();
+        Some(space_before_punctuation.replace_all(&text, "$1").to_string())
+    }
+}
+
+pub struct CollapseWhitespace;
+
+impl TextFilter for CollapseWhitespace {
+    fn apply(&self, text: String) -> Option<String> {
+        let regex = Regex::new(r"\s+").unwrap();
+        Some(regex.replace_all(text.trim(), " ").to_string())
+    }
+}
+
+pub struct RejectShortSentences {
+    pub min_words: usize
+}
+
+impl TextFilter for RejectShortSentences {
+    fn apply(&self, text: String) -> Option<String> {
+        let text: String = split_sentences(&text)
+            .into_iter()
+            .filter(|sentence| sentence.split_whitespace().count() >= self.min_words)
+            .collect();
+
+        Some(text.trim_end().to_string())
+    }
+}
+
+const ABBREVIATIONS: [&str; 16] = ["Dr", "Mr", "Mrs", "Ms", "Prof", "St", "Jr", "Sr", "Mt", "No", "Co", "Inc", "Ltd", "vs", "etc", "approx"];
+
+fn is_abbreviation(text: &str) -> bool {
+    let Some(word) = text.split_whitespace().last() else {
+        return false;
+    };
+    let word = word.trim_start_matches(['"', '\'', '(']);
+
+    word.contains('.') || (word.chars().count() == 1 && word.chars().all(char::is_alphabetic)) || ABBREVIATIONS.contains(&word)
+}
+
+pub fn split_sentences(text: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut sentences = Vec::new();
+    let mut start = 0;
+
+    for (i, &(index, char)) in chars.iter().enumerate() {
+        if !matches!(char, '.' | '!' | '?') || !chars.get(i + 1).is_some_and(|(_, next)| next.is_whitespace()) {
+            continue;
+        }
+
+        let Some(&(next_start, next)) = chars[i + 1..].iter().find(|(_, next)| !next.is_whitespace()) else {
+            continue;
+        };
+
+        if !next.is_uppercase() || (char == '.' && is_abbreviation(&text[start..index])) {
+            continue;
+        }
+
+        sentences.push(&text[start..next_start]);
+        start = next_start;
+    }
+
+    if start < text.len() {
+        sentences.push(&text[start..]);
+    }
+
+    sentences
+}
+
+pub struct CompleteSentences;
+
+impl TextFilter for CompleteSentences {
+    fn apply(&self, text: String) -> Option<String> {
+        let mut sentences = split_sentences(&text);
+
+        if sentences.last().is_some_and(|sentence| !sentence.trim_end().trim_end_matches(['"', '\'']).ends_with(['.', '!', '?'])) {
+            sentences.pop();
+        }
+
+        let text = sentences.concat().trim_end().to_string();
+        if text.is_empty() { None } else { Some(text) }
+    }
+}
+
+pub struct CleaningPipeline {
+    filters: Vec<Box<dyn TextFilter>>
+}
+
+impl CleaningPipeline {
+    pub fn new() -> Self {
+        CleaningPipeline { filters: Vec::new() }
+    }
+
+    pub fn with(mut self, filter: impl TextFilter + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    pub fn apply(&self, text: String) -> Option<String> {
+        self.filters.iter().try_fold(text, |text, filter| filter.apply(text))
+    }
+}
+
+impl Default for CleaningPipeline {
+    fn default() -> Self {
+        CleaningPipeline::new()
+            .with(NormalizePunctuation)
+            .with(RemoveHeadlines)
+            .with(DropListLines)
+            .with(RemoveParentheticals)
+            .with(RejectNonAscii)
+            .with(CollapseWhitespace)
+            .with(RejectShortSentences { min_words: 4 })
+            .with(CompleteSentences)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EINSTEIN: &str = "Albert Einstein (14 March 1879 \u{2013} 18 April 1955) was a German-born theoretical physicist who is best known for developing the theory of relativity. Einstein also made important contributions to quantum mechanics.";
+    const PYTHON: &str = "Python is a high-level, general-purpose programming language.\n\n== History ==\nIts design philosophy emphasizes code readability with the use of significant indentation.";
+    const TOKYO: &str = "Tokyo (\u{6771}\u{4EAC}, T\u{14D}ky\u{14D}) is the capital of Japan.";
+    const ARMY: &str = "The U.S. Army was founded in 1775 by the Continental Congress.";
+    const SMITH: &str = "Dr. Smith studied chemistry at the University of Edinburgh. He later taught in London.";
+
+    #[test]
+    fn normalize_punctuation_replaces_typographic_characters() {
+        let text = "It was called \u{201C}the Big Apple\u{201D} \u{2013} the city\u{2019}s nickname\u{2026}".to_string();
+        assert_eq!(NormalizePunctuation.apply(text).unwrap(), "It was called \"the Big Apple\" - the city's nickname...");
+    }
+
+    #[test]
+    fn reject_non_ascii_rejects_foreign_scripts() {
+        assert!(RejectNonAscii.apply(TOKYO.to_string()).is_none());
+        assert_eq!(RejectNonAscii.apply(ARMY.to_string()).unwrap(), ARMY);
+    }
+
+    #[test]
+    fn remove_headlines_strips_section_titles() {
+        let text = RemoveHeadlines.apply(PYTHON.to_string()).unwrap();
+        assert!(!text.contains("History"));
+        assert!(text.contains("Its design philosophy"));
+    }
+
+    #[test]
+    fn drop_list_lines_keeps_only_prose() {
+        let text = "Mercury is the first planet from the Sun.\n- Venus\n1. Earth\nSee also".to_string();
+        assert_eq!(DropListLines.apply(text).unwrap(), "Mercury is the first planet from the Sun.");
+    }
+
+    #[test]
+    fn remove_parentheticals_strips_dates_and_citations() {
+        let text = "Marie Curie (n\u{e9}e Sk\u{142}odowska; 1867 (in Warsaw)) was a physicist[1] and chemist.".to_string();
+        assert_eq!(RemoveParentheticals.apply(text).unwrap(), "Marie Curie was a physicist and chemist.");
+    }
+
+    #[test]
+    fn collapse_whitespace_joins_lines() {
+        let text = "  Python is a high-level language.\n\n  It is widely used.  ".to_string();
+        assert_eq!(CollapseWhitespace.apply(text).unwrap(), "Python is a high-level language. It is widely used.");
+    }
+
+    #[test]
+    fn reject_short_sentences_drops_fragments() {
+        let text = "Mercury is the first planet from the Sun. See below. It has no moons at all.".to_string();
+        let filter = RejectShortSentences { min_words: 4 };
+        assert_eq!(filter.apply(text).unwrap(), "Mercury is the first planet from the Sun. It has no moons at all.");
+    }
+
+    #[test]
+    fn reject_short_sentences_keeps_abbreviations() {
+        let filter = RejectShortSentences { min_words: 4 };
+        assert_eq!(filter.apply(ARMY.to_string()).unwrap(), ARMY);
+        assert_eq!(filter.apply(SMITH.to_string()).unwrap(), SMITH);
+    }
+
+    #[test]
+    fn complete_sentences_cuts_trailing_fragment() {
+        let text = "Einstein also made important contributions to quantum mechanics. He was".to_string();
+        assert_eq!(CompleteSentences.apply(text).unwrap(), "Einstein also made important contributions to quantum mechanics.");
+        assert!(CompleteSentences.apply("the quick brown fox".to_string()).is_none());
+        assert_eq!(CompleteSentences.apply(format!("{} It was renamed by the U.S. Army", ARMY)).unwrap(), ARMY);
+    }
+
+    #[test]
+    fn default_pipeline_cleans_extracts() {
+        let pipeline = CleaningPipeline::default();
+
+        assert_eq!(
+            pipeline.apply(EINSTEIN.to_string()).unwrap(),
+            "Albert Einstein was a German-born theoretical physicist who is best known for developing the theory of relativity. Einstein also made important contributions to quantum mechanics."
+        );
+        assert_eq!(
+            pipeline.apply(PYTHON.to_string()).unwrap(),
+            "Python is a high-level, general-purpose programming language. Its design philosophy emphasizes code readability with the use of significant indentation."
+        );
+        assert_eq!(pipeline.apply(ARMY.to_string()).unwrap(), ARMY);
+        assert_eq!(pipeline.apply(SMITH.to_string()).unwrap(), SMITH);
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::Path;
+
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use serde::Serialize;
+use tracing::warn;
+
+pub const DEFAULT_SNIPPET_DIR: &str = "snippets";
+pub const INDENT_WIDTH: usize = 4;
+pub const MAX_SNIPPET_LEN: usize = 1500;
+
+const LANGUAGE_EXTENSIONS: [(&str, &str); 14] = [
+    ("rs", "rust"),
+    ("py", "python"),
+    ("js", "javascript"),
+    ("ts", "typescript"),
+    ("go", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cpp", "cpp"),
+    ("cs", "csharp"),
+    ("rb", "ruby"),
+    ("swift", "swift"),
+    ("php", "php")
+];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CodeSnippet {
+    pub title: String,
+    pub language: String,
+    pub text: String
+}
+
+#[derive(Default)]
+pub struct SnippetStore {
+    pub snippets: Vec<CodeSnippet>
+}
+
+impl SnippetStore {
+    pub fn load(dir: impl AsRef<Path>) -> Self {
+        let entries = match fs::read_dir(dir.as_ref()) {
+            Ok(entries) => entries,
+            Err(error) => {
+                warn!("Could not read snippet directory {}: {}", dir.as_ref().display(), error);
+                return SnippetStore::default();
+            }
+        };
+
+        let snippets = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let path = entry.path();
+                let extension = path.extension()?.to_str()?;
+                let (_, language) = LANGUAGE_EXTENSIONS.iter().find(|(known, _)| *known == extension)?;
+                let text = normalize_code(&fs::read_to_string(&path).ok()?)?;
+
+                Some(CodeSnippet {
+                    title: path.file_name()?.to_string_lossy().to_string(),
+                    language: language.to_string(),
+                    text
+                })
+            })
+            .collect();
+
+        SnippetStore { snippets }
+    }
+
+    pub fn random_snippet(&self, language: Option<&str>) -> Option<CodeSnippet> {
+        let candidates: Vec<&CodeSnippet> = self.snippets
+            .iter()
+            .filter(|snippet| language.is_none_or(|language| snippet.language == language))
+            .collect();
+
+        candidates.choose(&mut thread_rng()).map(|snippet| (*snippet).clone())
+    }
+}
+
+fn normalize_code(source: &str) -> Option<String> {
+    if !source.is_ascii() {
+        return None;
+    }
+
+    let text = source
+        .replace("\r\n", "\n")
+        .replace('\t', &" ".repeat(INDENT_WIDTH))
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string();
+
+    if text.is_empty() || text.len() > MAX_SNIPPET_LEN || text.chars().any(|char| char.is_control() && char != '\n') {
+        return None;
+    }
+
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_tabs_and_line_endings() {
+        let source = "fn main() {\r\n\tprintln!(\"hi\");   \r\n}\r\n";
+        assert_eq!(normalize_code(source).unwrap(), "fn main() {\n    println!(\"hi\");\n}");
+    }
+
+    #[test]
+    fn rejects_invalid_snippets() {
+        assert!(normalize_code("let café = 1;").is_none());
+        assert!(normalize_code("\n\n").is_none());
+        assert!(normalize_code("let bell = '\u{7}';").is_none());
+        assert!(normalize_code(&"x".repeat(MAX_SNIPPET_LEN + 1)).is_none());
+    }
+}
+
@@ -0,0 +1,81 @@
+use serde::Serialize;
+
+use crate::text::cleaning::{CleaningPipeline, CollapseWhitespace, NormalizePunctuation, RejectNonAscii};
+
+pub const MAX_CUSTOM_TEXTS: usize = 20;
+pub const CUSTOM_TEXT_MIN_LEN: usize = 20;
+pub const CUSTOM_TEXT_MAX_LEN: usize = 2000;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum CustomTextError {
+    Empty,
+    TooMany,
+    TooShort,
+    TooLong,
+    InvalidCharacters
+}
+
+fn custom_text_pipeline() -> CleaningPipeline {
+    CleaningPipeline::new()
+        .with(NormalizePunctuation)
+        .with(RejectNonAscii)
+        .with(CollapseWhitespace)
+}
+
+pub fn clean_custom_text(text: &str) -> Result<String, CustomTextError> {
+    if text.chars().any(|char| char.is_control() && !char.is_whitespace()) {
+        return Err(CustomTextError::InvalidCharacters);
+    }
+
+    let cleaned = custom_text_pipeline()
+        .apply(text.to_string())
+        .ok_or(CustomTextError::InvalidCharacters)?;
+
+    let length = cleaned.chars().count();
+    if length < CUSTOM_TEXT_MIN_LEN {
+        return Err(CustomTextError::TooShort);
+    }
+
+    if length > CUSTOM_TEXT_MAX_LEN {
+        return Err(CustomTextError::TooLong);
+    }
+
+    Ok(cleaned)
+}
+
+pub fn clean_custom_texts(texts: &[String]) -> Result<Vec<String>, CustomTextError> {
+    if texts.is_empty() {
+        return Err(CustomTextError::Empty);
+    }
+
+    if texts.len() > MAX_CUSTOM_TEXTS {
+        return Err(CustomTextError::TooMany);
+    }
+
+    texts.iter().map(|text| clean_custom_text(text)).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_text_without_punctuation() {
+        let text = "the quick brown fox jumps over the lazy dog";
+        assert_eq!(clean_custom_text(text), Ok(text.to_string()));
+    }
+
+    #[test]
+    fn keeps_list_lines_and_collapses_whitespace() {
+        let text = "- first drill line\n- second drill line\n";
+        assert_eq!(clean_custom_text(text), Ok("- first drill line - second drill line".to_string()));
+    }
+
+    #[test]
+    fn rejects_invalid_texts() {
+        assert_eq!(clean_custom_text("Tokyo \u{6771}\u{4EAC} is the capital of Japan"), Err(CustomTextError::InvalidCharacters));
+        assert_eq!(clean_custom_text("too short"), Err(CustomTextError::TooShort));
+        assert_eq!(clean_custom_texts(&[]), Err(CustomTextError::Empty));
+    }
+}
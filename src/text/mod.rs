@@ -1 +1,7 @@
+pub mod cache;
+pub mod cleaning;
+pub mod code;
+pub mod custom;
+pub mod pool;
+pub mod quality;
 pub mod wikipedia;
\ No newline at end of file
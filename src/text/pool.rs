@@ -0,0 +1,137 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use tokio::sync::RwLock;
+use tokio::time::sleep;
+
+use crate::states::app_state::SharedAppState;
+use crate::states::game_state::TEXT_SIZE;
+use crate::text::cache::{Passage, TextSource};
+use crate::text::wikipedia::Language;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, EnumIter)]
+pub enum TextLength {
+    Short,
+    #[default]
+    Medium,
+    Long
+}
+
+impl TextLength {
+    pub fn size(&self) -> usize {
+        match self {
+            TextLength::Short => TEXT_SIZE / 2,
+            TextLength::Medium => TEXT_SIZE,
+            TextLength::Long => TEXT_SIZE * 2
+        }
+    }
+}
+
+pub type PassageIdMap = HashMap<(Language, TextLength), VecDeque<String>>;
+
+pub const POOL_SIZE: usize = 5;
+pub const POOL_REFILL_INTERVAL: Duration = Duration::from_secs(1);
+pub const POOL_REFILL_COOLDOWN: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+pub struct TextPool {
+    pub texts: RwLock<PassageIdMap>
+}
+
+impl TextPool {
+    pub async fn take(&self, language: Language, length: TextLength, exclude: &HashSet<String>) -> Option<String> {
+        let mut binding = self.texts.write().await;
+        let passage_ids = binding.get_mut(&(language, length))?;
+        let index = passage_ids.iter().position(|passage_id| !exclude.contains(passage_id))?;
+        passage_ids.remove(index)
+    }
+
+    pub async fn add(&self, language: Language, length: TextLength, passage_id: String) {
+        let mut binding = self.texts.write().await;
+        let passage_ids = binding.entry((language, length)).or_default();
+
+        if passage_ids.len() < POOL_SIZE {
+            passage_ids.push_back(passage_id);
+        }
+    }
+
+    pub async fn find_missing(&self, skip: &HashSet<(Language, TextLength)>) -> Option<(Language, TextLength)> {
+        let binding = self.texts.read().await;
+
+        Language::iter()
+            .flat_map(|language| TextLength::iter().map(move |length| (language, length)))
+            .filter(|key| !skip.contains(key))
+            .find(|key| binding.get(key).map_or(0, VecDeque::len) < POOL_SIZE)
+    }
+}
+
+pub async fn refill_text_pool(state: SharedAppState) {
+    let mut cooldowns: HashMap<(Language, TextLength), Instant> = HashMap::new();
+
+    loop {
+        cooldowns.retain(|_, until| *until > Instant::now());
+        let skip = cooldowns.keys().copied().collect();
+
+        let state_guard = state.lock().await;
+        let missing = state_guard.texts.find_missing(&skip).await;
+        let wikipedia = state_guard.wikipedia.clone();
+        drop(state_guard);
+
+        match missing {
+            Some((language, length)) => {
+                let extracts = wikipedia.generate_texts(language, length.size()).await;
+
+                if extracts.is_empty() {
+                    cooldowns.insert((language, length), Instant::now() + POOL_REFILL_COOLDOWN);
+                    continue;
+                }
+
+                let state_guard = state.lock().await;
+                for extract in extracts {
+                    let passage = state_guard.passages.add_passage(TextSource::Wikipedia, extract.title, language, None, extract.value).await;
+                    state_guard.texts.add(language, length, passage.id).await;
+                }
+            }
+            None => sleep(POOL_REFILL_INTERVAL).await
+        }
+    }
+}
+
+pub async fn take_or_generate_text(state: &SharedAppState, language: Language, length: TextLength, players: &[String]) -> Passage {
+    let state_guard = state.lock().await;
+    let recent = state_guard.passages.recently_played(players).await;
+
+    if let Some(passage_id) = state_guard.texts.take(language, length, &recent).await {
+        if let Some(passage) = state_guard.passages.get_passage(&passage_id).await {
+            return passage;
+        }
+    }
+
+    let wikipedia = state_guard.wikipedia.clone();
+    drop(state_guard);
+
+    let (best, extracts) = loop {
+        let mut extracts = wikipedia.generate_texts(language, length.size()).await.into_iter();
+        if let Some(best) = extracts.next() {
+            break (best, extracts);
+        }
+
+        let state_guard = state.lock().await;
+        if let Some(passage_id) = state_guard.texts.take(language, length, &HashSet::new()).await {
+            if let Some(passage) = state_guard.passages.get_passage(&passage_id).await {
+                return passage;
+            }
+        }
+    };
+
+    let state_guard = state.lock().await;
+    for extract in extracts {
+        let passage = state_guard.passages.add_passage(TextSource::Wikipedia, extract.title, language, None, extract.value).await;
+        state_guard.texts.add(language, length, passage.id).await;
+    }
+
+    state_guard.passages.add_passage(TextSource::Wikipedia, best.title, language, None, best.value).await
+}
@@ -0,0 +1,114 @@
+use serde::Serialize;
+
+pub const MIN_QUALITY_SCORE: f64 = 0.6;
+const COMMON_SYMBOLS: &str = ".,;:'\"!?-";
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct TextQuality {
+    pub score: f64,
+    pub difficulty: f64,
+    pub letter_ratio: f64,
+    pub digit_ratio: f64,
+    pub uppercase_ratio: f64,
+    pub average_word_length: f64,
+    pub rare_symbols: usize
+}
+
+impl TextQuality {
+    pub fn is_acceptable(&self) -> bool {
+        self.score >= MIN_QUALITY_SCORE
+    }
+}
+
+pub fn rate_text(text: &str) -> TextQuality {
+    let characters = text.chars().filter(|char| !char.is_whitespace()).count().max(1) as f64;
+    let letters = text.chars().filter(|char| char.is_alphabetic()).count() as f64;
+    let digits = text.chars().filter(|char| char.is_ascii_digit()).count() as f64;
+    let uppercase = text.chars().filter(|char| char.is_uppercase()).count() as f64;
+    let symbols = text.chars().filter(|char| COMMON_SYMBOLS.contains(*char)).count() as f64;
+    let rare_symbols = text.chars()
+        .filter(|char| !char.is_alphanumeric() && !char.is_whitespace() && !COMMON_SYMBOLS.contains(*char))
+        .count();
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let average_word_length = words.iter()
+        .map(|word| word.trim_matches(|char: char| !char.is_alphanumeric()).chars().count())
+        .sum::<usize>() as f64 / words.len().max(1) as f64;
+
+    let letter_ratio = letters / characters;
+    let digit_ratio = digits / characters;
+    let uppercase_ratio = uppercase / characters;
+
+    let mut score = 1.0;
+    score -= digit_ratio * 5.0;
+    score -= (uppercase_ratio - 0.05).max(0.0) * 3.0;
+    score -= (0.8 - letter_ratio).max(0.0) * 2.0;
+    score -= rare_symbols as f64 * 0.05;
+    score -= (average_word_length - 6.5).max(0.0) * 0.2;
+    score -= (3.5 - average_word_length).max(0.0) * 0.2;
+
+    let difficulty = (average_word_length - 3.0) * 1.5
+        + symbols / characters * 40.0
+        + uppercase_ratio * 20.0
+        + digit_ratio * 30.0
+        + rare_symbols as f64 * 0.5;
+
+    TextQuality {
+        score: score.clamp(0.0, 1.0),
+        difficulty: difficulty.clamp(0.0, 10.0),
+        letter_ratio,
+        digit_ratio,
+        uppercase_ratio,
+        average_word_length,
+        rare_symbols
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROSE: &str = "Albert Einstein was a German-born theoretical physicist who is best known for developing the theory of relativity. Einstein also made important contributions to quantum mechanics.";
+    const DIGITS: &str = "In 1879, 1880, 1881 and 1882 the measured values were 12, 345, 6789 and 101112 with 2.5 and 7.25 in 1990.";
+    const UPPERCASE: &str = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG AND RUNS AWAY FROM THE FARM BEFORE DAWN.";
+    const SYMBOLS: &str = "The value {x} is <y> and @z with #w, $v, %u, ^t, &s, *r, ~q and |p in the result.";
+
+    #[test]
+    fn accepts_normal_prose() {
+        let quality = rate_text(PROSE);
+        assert!(quality.is_acceptable(), "{:?}", quality);
+        assert_eq!(quality.rare_symbols, 0);
+    }
+
+    #[test]
+    fn rejects_digit_heavy_text() {
+        let quality = rate_text(DIGITS);
+        assert!(!quality.is_acceptable(), "{:?}", quality);
+        assert!(quality.digit_ratio > rate_text(PROSE).digit_ratio);
+    }
+
+    #[test]
+    fn rejects_uppercase_text() {
+        let quality = rate_text(UPPERCASE);
+        assert!(!quality.is_acceptable(), "{:?}", quality);
+        assert!(quality.uppercase_ratio > 0.9);
+    }
+
+    #[test]
+    fn rejects_symbol_heavy_text() {
+        let quality = rate_text(SYMBOLS);
+        assert!(!quality.is_acceptable(), "{:?}", quality);
+        assert_eq!(quality.rare_symbols, 13);
+    }
+
+    #[test]
+    fn scores_and_difficulty_stay_in_range() {
+        for text in [PROSE, DIGITS, UPPERCASE, SYMBOLS, "", "a", "$$$$ #### @@@@ %%%%"] {
+            let quality = rate_text(text);
+            assert!((0.0..=1.0).contains(&quality.score), "{:?}", quality);
+            assert!((0.0..=10.0).contains(&quality.difficulty), "{:?}", quality);
+        }
+
+        assert!(rate_text(SYMBOLS).difficulty > rate_text(PROSE).difficulty);
+    }
+}
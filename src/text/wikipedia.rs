@@ -1,8 +1,15 @@
 use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
 
-use regex::Regex;
-use reqwest::Error;
-use serde::Deserialize;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+use tokio::time::sleep;
+use tracing::warn;
+
+use crate::text::cleaning::{split_sentences, CleaningPipeline};
+use crate::text::quality::rate_text;
 
 #[derive(Deserialize, Debug)]
 struct ApiResponse {
@@ -11,84 +18,269 @@ struct ApiResponse {
 
 #[derive(Deserialize, Debug)]
 struct Query {
-    random: Option<Vec<Random>>,
     pages: Option<HashMap<String, Page>>
 }
 
 #[derive(Deserialize, Debug)]
 struct Page {
-    extract: String,
+    title: String,
+    #[serde(default)]
+    extract: String
 }
 
-#[derive(Deserialize, Debug)]
-struct Random {
-    title: String
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, EnumIter)]
+pub enum Language {
+    #[default]
+    English,
+    SimpleEnglish
 }
 
+impl Language {
+    pub fn subdomain(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::SimpleEnglish => "simple"
+        }
+    }
+}
+
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_ATTEMPTS: u32 = 5;
+const BATCH_SIZE: usize = 10;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+pub const DEFAULT_BASE_URL: &str = "https://{language}.wikipedia.org/w/api.php";
+
 pub struct WikipediaResponse {
     pub title: String,
     pub value: String
 }
 
-pub async fn get_random_article_extract() -> Result<WikipediaResponse, Error> {
-    let random_page_response = reqwest::get("https://en.wikipedia.org/w/api.php?action=query&format=json&list=random&rnnamespace=0&rnlimit=1")
-        .await?
-        .json::<ApiResponse>()
-        .await?;
-
-    let page_name = random_page_response.query.random.unwrap()[0].title.clone();
+#[derive(Debug)]
+pub enum WikipediaError {
+    Request(reqwest::Error),
+    MissingData(&'static str)
+}
 
-    let url = "https://en.wikipedia.org/w/api.php?action=query&format=json&prop=extracts&titles=".to_string() + &page_name + "&explaintext=true";
+impl Display for WikipediaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            WikipediaError::Request(error) => write!(f, "request failed: {}", error),
+            WikipediaError::MissingData(field) => write!(f, "response is missing {}", field)
+        }
+    }
+}
 
-    let extract_response = reqwest::get(url)
-        .await?
-        .json::<ApiResponse>()
-        .await?;
+impl From<reqwest::Error> for WikipediaError {
+    fn from(error: reqwest::Error) -> Self {
+        WikipediaError::Request(error)
+    }
+}
 
-    let extract = extract_response.query.pages.unwrap().values().next().ok_or("No pages found").unwrap().extract.clone();
+#[derive(Clone, Debug)]
+pub struct WikipediaClient {
+    client: Client,
+    base_url: String
+}
 
-    Ok(WikipediaResponse {title: page_name, value: extract})
+impl Default for WikipediaClient {
+    fn default() -> Self {
+        WikipediaClient::new(DEFAULT_BASE_URL)
+    }
 }
 
-pub fn get_pretty_extract(mut extract: String) -> Option<String> {
-    let mut equal_sign_count = 0;
-    let mut end_headline = 0;
+impl WikipediaClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .user_agent(USER_AGENT)
+            .build()
+            .unwrap();
 
-    let mut i = extract.chars().count();
-    for char in extract.clone().chars().rev() {
-        if !char.is_ascii() {
-            return None
-        }
-        
-        i -= 1;
-
-        if char == '=' {
-            equal_sign_count += 1;
-        } else if char == '\n' {
-            if equal_sign_count >= 2 && end_headline != 0 {
-                let start = extract
-                    .char_indices()
-                    .nth(i + 1)
-                    .map(|(pos, _)| pos)
-                    .unwrap();
-
-                let end = extract
-                    .char_indices()
-                    .nth(end_headline)
-                    .map(|(pos, char)| pos + char.len_utf8())
-                    .unwrap();
-
-                extract.replace_range(start..end, "");
+        WikipediaClient { client, base_url: base_url.into() }
+    }
+
+    fn api_url(&self, language: Language) -> String {
+        self.base_url.replace("{language}", language.subdomain())
+    }
+
+    async fn query(&self, language: Language, params: &[(&str, &str)]) -> Result<Query, WikipediaError> {
+        let response = self.client.get(self.api_url(language))
+            .query(&[("action", "query"), ("format", "json")])
+            .query(params)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ApiResponse>()
+            .await?;
+
+        Ok(response.query)
+    }
+
+    pub async fn get_random_article_extracts(&self, language: Language) -> Result<Vec<WikipediaResponse>, WikipediaError> {
+        let batch_size = BATCH_SIZE.to_string();
+        let query = self.query(language, &[
+            ("generator", "random"),
+            ("grnnamespace", "0"),
+            ("grnlimit", &batch_size),
+            ("prop", "extracts"),
+            ("exintro", "true"),
+            ("explaintext", "true"),
+            ("exlimit", "max")
+        ]).await?;
+
+        let pages = query.pages.ok_or(WikipediaError::MissingData("pages"))?;
+
+        Ok(pages.into_values()
+            .map(|page| WikipediaResponse { title: page.title, value: page.extract })
+            .collect())
+    }
+
+    pub async fn generate_texts(&self, language: Language, size: usize) -> Vec<WikipediaResponse> {
+        let pipeline = CleaningPipeline::default();
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            if attempt > 1 {
+                sleep(RETRY_DELAY * 2u32.pow(attempt - 2)).await;
+            }
+
+            let wikipedia_responses = match self.get_random_article_extracts(language).await {
+                Ok(wikipedia_responses) => wikipedia_responses,
+                Err(error) => {
+                    warn!("Failed to fetch Wikipedia extracts: {}", error);
+                    continue;
+                }
+            };
+
+            let mut candidates: Vec<(f64, WikipediaResponse)> = wikipedia_responses.into_iter()
+                .filter_map(|wikipedia_response| {
+                    let raw_end = sentence_end(&wikipedia_response.value, size * 2);
+                    let cleaned = pipeline.apply(wikipedia_response.value[..raw_end].to_string())?;
+
+                    if cleaned.chars().count() < size {
+                        return None;
+                    }
+
+                    let end = sentence_end(&cleaned, size);
+                    let value = cleaned[..end].trim_end().to_string();
+                    let quality = rate_text(&value);
+
+                    quality.is_acceptable().then_some((quality.score, WikipediaResponse { title: wikipedia_response.title, value }))
+                })
+                .collect();
+
+            if !candidates.is_empty() {
+                candidates.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+                return candidates.into_iter().map(|(_, candidate)| candidate).collect();
             }
+        }
+
+        warn!("No usable {:?} Wikipedia extracts of {} characters after {} attempts", language, size, MAX_ATTEMPTS);
+        Vec::new()
+    }
+}
 
-            equal_sign_count = 0;
-            end_headline = 0;
-        } else if equal_sign_count >= 2 {
-            end_headline = i + equal_sign_count;
-            equal_sign_count = 0;
+fn sentence_end(text: &str, min_chars: usize) -> usize {
+    let mut end = 0;
+    let mut chars = 0;
+
+    for sentence in split_sentences(text) {
+        if chars >= min_chars {
+            break;
         }
+
+        end += sentence.len();
+        chars += sentence.chars().count();
+    }
+
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::http::{StatusCode, Uri};
+    use axum::routing::get;
+    use axum::Router;
+    use tokio::net::TcpListener;
+    use tokio::sync::Mutex;
+
+    use super::*;
+
+    const EXTRACTS: &str = r#"{"batchcomplete":"","query":{"pages":{"123":{"pageid":123,"ns":0,"title":"Rock & Roll Café","extract":"Rock and roll is a genre of popular music."}}}}"#;
+
+    async fn mock_server(status: StatusCode, body: &'static str) -> (String, Arc<Mutex<Vec<Uri>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+
+        let app = Router::new().route("/:language/w/api.php", get(move |uri: Uri| async move {
+            recorded.lock().await.push(uri);
+            (status, body)
+        }));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{}/{{language}}/w/api.php", address), requests)
     }
 
-    let regex = Regex::new(r"\s+").unwrap();
-    Some(regex.replace_all(&extract, " ").to_string())
-}
\ No newline at end of file
+    #[test]
+    fn sentence_end_keeps_whole_sentences() {
+        let text = "The U.S. Army was founded in 1775. Dr. Smith joined it in version 3.5 of the plan. It grew quickly.";
+
+        assert_eq!(&text[..sentence_end(text, 10)], "The U.S. Army was founded in 1775. ");
+        assert_eq!(&text[..sentence_end(text, 40)], "The U.S. Army was founded in 1775. Dr. Smith joined it in version 3.5 of the plan. ");
+        assert_eq!(sentence_end(text, 1000), text.len());
+    }
+
+    #[tokio::test]
+    async fn query_encodes_parameters() {
+        let (base_url, requests) = mock_server(StatusCode::OK, EXTRACTS).await;
+        let client = WikipediaClient::new(base_url);
+
+        client.query(Language::SimpleEnglish, &[("titles", "Rock & Roll Café")]).await.unwrap();
+
+        let uri = requests.lock().await[0].clone();
+        assert_eq!(uri.path(), "/simple/w/api.php");
+        assert_eq!(uri.query().unwrap(), "action=query&format=json&titles=Rock+%26+Roll+Caf%C3%A9");
+    }
+
+    #[tokio::test]
+    async fn random_extracts_are_parsed() {
+        let (base_url, requests) = mock_server(StatusCode::OK, EXTRACTS).await;
+        let client = WikipediaClient::new(base_url);
+
+        let extracts = client.get_random_article_extracts(Language::English).await.unwrap();
+
+        assert_eq!(extracts.len(), 1);
+        assert_eq!(extracts[0].title, "Rock & Roll Café");
+        assert_eq!(extracts[0].value, "Rock and roll is a genre of popular music.");
+
+        let uri = requests.lock().await[0].clone();
+        assert_eq!(uri.path(), "/en/w/api.php");
+        assert!(uri.query().unwrap().contains("generator=random&grnnamespace=0&grnlimit=10"));
+    }
+
+    #[tokio::test]
+    async fn error_status_is_a_request_error() {
+        let (base_url, _) = mock_server(StatusCode::SERVICE_UNAVAILABLE, "").await;
+        let client = WikipediaClient::new(base_url);
+
+        let result = client.get_random_article_extracts(Language::English).await;
+        assert!(matches!(result, Err(WikipediaError::Request(error)) if error.status() == Some(reqwest::StatusCode::SERVICE_UNAVAILABLE)));
+    }
+
+    #[tokio::test]
+    async fn missing_pages_is_missing_data() {
+        let (base_url, _) = mock_server(StatusCode::OK, r#"{"batchcomplete":"","query":{}}"#).await;
+        let client = WikipediaClient::new(base_url);
+
+        let result = client.get_random_article_extracts(Language::English).await;
+        assert!(matches!(result, Err(WikipediaError::MissingData("pages"))));
+    }
+}
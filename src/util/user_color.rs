@@ -1,7 +1,11 @@
-use serde::Serialize;
-use strum_macros::EnumIter;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use strum::IntoEnumIterator;
+use strum_macros::{EnumIter, IntoStaticStr};
 
-#[derive(Debug, Clone, Copy, Serialize, EnumIter)]
+const NEAR_WHITE_MIN_CHANNEL: u8 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, EnumIter, IntoStaticStr)]
 pub enum UserColor {
     AliceBlue,
     AntiqueWhite,
@@ -112,3 +116,244 @@ pub enum UserColor {
     Yellow,
     YellowGreen,
 }
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Palette {
+    #[default]
+    All,
+    LightBackground,
+    DarkBackground
+}
+
+impl Palette {
+    pub fn colors(&self) -> Vec<UserColor> {
+        match self {
+            Palette::All => {
+                let mut colors: Vec<UserColor> = Vec::new();
+
+                for color in UserColor::iter().filter(|color| !color.is_near_white()) {
+                    if colors.iter().all(|chosen| chosen.rgb() != color.rgb()) {
+                        colors.push(color);
+                    }
+                }
+
+                colors
+            }
+            Palette::LightBackground => vec![
+                UserColor::Blue,
+                UserColor::BlueViolet,
+                UserColor::CadetBlue,
+                UserColor::Chocolate,
+                UserColor::Crimson,
+                UserColor::DarkCyan,
+                UserColor::DarkGoldenRod,
+                UserColor::DarkViolet,
+                UserColor::DeepPink,
+                UserColor::DodgerBlue,
+                UserColor::FireBrick,
+                UserColor::ForestGreen,
+                UserColor::Fuchsia,
+                UserColor::IndianRed,
+                UserColor::MediumVioletRed,
+                UserColor::OliveDrab,
+                UserColor::OrangeRed,
+                UserColor::PaleVioletRed,
+                UserColor::RebeccaPurple,
+                UserColor::RoyalBlue,
+                UserColor::SeaGreen,
+                UserColor::Sienna,
+                UserColor::SteelBlue
+            ],
+            Palette::DarkBackground => vec![
+                UserColor::Aqua,
+                UserColor::Aquamarine,
+                UserColor::Chartreuse,
+                UserColor::Coral,
+                UserColor::DarkOrange,
+                UserColor::DarkSalmon,
+                UserColor::DarkTurquoise,
+                UserColor::DeepSkyBlue,
+                UserColor::Gold,
+                UserColor::GoldenRod,
+                UserColor::GreenYellow,
+                UserColor::HotPink,
+                UserColor::Khaki,
+                UserColor::LawnGreen,
+                UserColor::LightCoral,
+                UserColor::LightSalmon,
+                UserColor::LightSeaGreen,
+                UserColor::LightSkyBlue,
+                UserColor::Lime,
+                UserColor::LimeGreen,
+                UserColor::MediumAquaMarine,
+                UserColor::MediumSpringGreen,
+                UserColor::MediumTurquoise,
+                UserColor::Orange,
+                UserColor::Salmon,
+                UserColor::SandyBrown,
+                UserColor::SkyBlue,
+                UserColor::SpringGreen,
+                UserColor::Turquoise,
+                UserColor::Violet,
+                UserColor::Yellow,
+                UserColor::YellowGreen
+            ]
+        }
+    }
+}
+
+impl UserColor {
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        match self {
+            UserColor::AliceBlue => (240, 248, 255),
+            UserColor::AntiqueWhite => (250, 235, 215),
+            UserColor::Aqua => (0, 255, 255),
+            UserColor::Aquamarine => (127, 255, 212),
+            UserColor::Azure => (240, 255, 255),
+            UserColor::Beige => (245, 245, 220),
+            UserColor::Bisque => (255, 228, 196),
+            UserColor::BlanchedAlmond => (255, 235, 205),
+            UserColor::Blue => (0, 0, 255),
+            UserColor::BlueViolet => (138, 43, 226),
+            UserColor::BurlyWood => (222, 184, 135),
+            UserColor::CadetBlue => (95, 158, 160),
+            UserColor::Chartreuse => (127, 255, 0),
+            UserColor::Chocolate => (210, 105, 30),
+            UserColor::Coral => (255, 127, 80),
+            UserColor::CornflowerBlue => (100, 149, 237),
+            UserColor::Cornsilk => (255, 248, 220),
+            UserColor::Crimson => (220, 20, 60),
+            UserColor::Cyan => (0, 255, 255),
+            UserColor::DarkCyan => (0, 139, 139),
+            UserColor::DarkGoldenRod => (184, 134, 11),
+            UserColor::DarkKhaki => (189, 183, 107),
+            UserColor::DarkOrange => (255, 140, 0),
+            UserColor::DarkSalmon => (233, 150, 122),
+            UserColor::DarkSeaGreen => (143, 188, 143),
+            UserColor::DarkTurquoise => (0, 206, 209),
+            UserColor::DarkViolet => (148, 0, 211),
+            UserColor::DeepPink => (255, 20, 147),
+            UserColor::DeepSkyBlue => (0, 191, 255),
+            UserColor::DodgerBlue => (30, 144, 255),
+            UserColor::FireBrick => (178, 34, 34),
+            UserColor::FloralWhite => (255, 250, 240),
+            UserColor::ForestGreen => (34, 139, 34),
+            UserColor::Fuchsia => (255, 0, 255),
+            UserColor::Gainsboro => (220, 220, 220),
+            UserColor::GhostWhite => (248, 248, 255),
+            UserColor::Gold => (255, 215, 0),
+            UserColor::GoldenRod => (218, 165, 32),
+            UserColor::GreenYellow => (173, 255, 47),
+            UserColor::HoneyDew => (240, 255, 240),
+            UserColor::HotPink => (255, 105, 180),
+            UserColor::IndianRed => (205, 92, 92),
+            UserColor::Ivory => (255, 255, 240),
+            UserColor::Khaki => (240, 230, 140),
+            UserColor::Lavender => (230, 230, 250),
+            UserColor::LavenderBlush => (255, 240, 245),
+            UserColor::LawnGreen => (124, 252, 0),
+            UserColor::LemonChiffon => (255, 250, 205),
+            UserColor::LightBlue => (173, 216, 230),
+            UserColor::LightCoral => (240, 128, 128),
+            UserColor::LightCyan => (224, 255, 255),
+            UserColor::LightGoldenRodYellow => (250, 250, 210),
+            UserColor::LightGreen => (144, 238, 144),
+            UserColor::LightPink => (255, 182, 193),
+            UserColor::LightSalmon => (255, 160, 122),
+            UserColor::LightSeaGreen => (32, 178, 170),
+            UserColor::LightSkyBlue => (135, 206, 250),
+            UserColor::LightSteelBlue => (176, 196, 222),
+            UserColor::LightYellow => (255, 255, 224),
+            UserColor::Lime => (0, 255, 0),
+            UserColor::LimeGreen => (50, 205, 50),
+            UserColor::Linen => (250, 240, 230),
+            UserColor::Magenta => (255, 0, 255),
+            UserColor::MediumAquaMarine => (102, 205, 170),
+            UserColor::MediumSpringGreen => (0, 250, 154),
+            UserColor::MediumTurquoise => (72, 209, 204),
+            UserColor::MediumVioletRed => (199, 21, 133),
+            UserColor::MintCream => (245, 255, 250),
+            UserColor::MistyRose => (255, 228, 225),
+            UserColor::Moccasin => (255, 228, 181),
+            UserColor::NavajoWhite => (255, 222, 173),
+            UserColor::OldLace => (253, 245, 230),
+            UserColor::OliveDrab => (107, 142, 35),
+            UserColor::Orange => (255, 165, 0),
+            UserColor::OrangeRed => (255, 69, 0),
+            UserColor::Orchid => (218, 112, 214),
+            UserColor::PaleGoldenRod => (238, 232, 170),
+            UserColor::PaleGreen => (152, 251, 152),
+            UserColor::PaleTurquoise => (175, 238, 238),
+            UserColor::PaleVioletRed => (219, 112, 147),
+            UserColor::PapayaWhip => (255, 239, 213),
+            UserColor::PeachPuff => (255, 218, 185),
+            UserColor::Peru => (205, 133, 63),
+            UserColor::Pink => (255, 192, 203),
+            UserColor::Plum => (221, 160, 221),
+            UserColor::PowderBlue => (176, 224, 230),
+            UserColor::RebeccaPurple => (102, 51, 153),
+            UserColor::RosyBrown => (188, 143, 143),
+            UserColor::RoyalBlue => (65, 105, 225),
+            UserColor::Salmon => (250, 128, 114),
+            UserColor::SandyBrown => (244, 164, 96),
+            UserColor::SeaGreen => (46, 139, 87),
+            UserColor::Seashell => (255, 245, 238),
+            UserColor::Sienna => (160, 82, 45),
+            UserColor::SkyBlue => (135, 206, 235),
+            UserColor::Snow => (255, 250, 250),
+            UserColor::SpringGreen => (0, 255, 127),
+            UserColor::SteelBlue => (70, 130, 180),
+            UserColor::Tan => (210, 180, 140),
+            UserColor::Thistle => (216, 191, 216),
+            UserColor::Tomato => (255, 99, 71),
+            UserColor::Turquoise => (64, 224, 208),
+            UserColor::Violet => (238, 130, 238),
+            UserColor::Wheat => (245, 222, 179),
+            UserColor::White => (255, 255, 255),
+            UserColor::WhiteSmoke => (245, 245, 245),
+            UserColor::Yellow => (255, 255, 0),
+            UserColor::YellowGreen => (154, 205, 50)
+        }
+    }
+
+    pub fn is_near_white(&self) -> bool {
+        let (red, green, blue) = self.rgb();
+        red.min(green).min(blue) >= NEAR_WHITE_MIN_CHANNEL
+    }
+
+    pub fn hex(&self) -> String {
+        let (red, green, blue) = self.rgb();
+        format!("#{:02X}{:02X}{:02X}", red, green, blue)
+    }
+}
+
+impl Serialize for UserColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name: &'static str = self.into();
+        let (red, green, blue) = self.rgb();
+
+        let mut state = serializer.serialize_struct("UserColor", 3)?;
+        state.serialize_field("name", name)?;
+        state.serialize_field("hex", &self.hex())?;
+        state.serialize_field("rgb", &[red, green, blue])?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_palette_has_distinct_visible_colors() {
+        let colors = Palette::default().colors();
+
+        assert!(!colors.contains(&UserColor::AliceBlue));
+        assert!(!colors.contains(&UserColor::Snow));
+        assert!(colors.iter().all(|color| !color.is_near_white()));
+
+        for (i, color) in colors.iter().enumerate() {
+            assert!(colors[i + 1..].iter().all(|other| other.rgb() != color.rgb()), "{:?} is duplicated", color);
+        }
+    }
+}